@@ -1,11 +1,21 @@
 import asyncio
+import html
+import io
 import json
 import logging
-from datetime import datetime
-from typing import Dict, List, Optional
+import os
+import re
+import signal
+import time
+from collections import deque
+from datetime import date, datetime, timedelta, timezone
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+from typing import Awaitable, Callable, Deque, Dict, List, Optional, Tuple
+from urllib.parse import urlparse
 
 from telegram import Bot, Update
-from telegram.error import ChatMigrated, TelegramError
+from telegram.error import BadRequest, ChatMigrated, TelegramError
+from telegram.request import HTTPXRequest
 from telegram.ext import (
     Application,
     CommandHandler,
@@ -15,21 +25,30 @@ from telegram.ext import (
 )
 
 from scrapers import (
+    BackoffActiveError,
     DegewoScraper,
+    FilterCriteria,
     FlatDetails,
+    GenericScraper,
     GesobauScraper,
     GewobagScraper,
     HighTrafficError,
     InBerlinWohnenScraper,
+    ParsingError,
     StandardFields,
     StadtUndLandScraper,
     WebsiteUnavailableError,
     close_session,
-    load_seen_flats,
-    mark_flats_as_seen,
-    reset_seen_flats,
-    save_seen_flats,
+    configure_session,
+    drop_cross_source_duplicates,
+    parse_cutoff_date,
 )
+from geocoding import DEFAULT_GEOCODING_URL, Geocoder
+from metrics import Metrics, start_http_server
+from notifiers import DiscordNotifier, Notifier
+from send_queue import SendQueue
+from state import StateStore
+from storage import create_store
 
 # Configure logging - output to stdout only (no file) to minimize SD card writes
 # When running as systemd service, logs will be captured by journald
@@ -42,39 +61,739 @@ logging.basicConfig(
 logger = logging.getLogger(__name__)
 
 
+def is_markup_error(error: TelegramError) -> bool:
+    """Whether Telegram rejected a message because of its HTML markup."""
+    message = str(error).lower()
+    return "can't parse entities" in message or "unsupported start tag" in message
+
+
+async def with_retries(
+    attempt: Callable[[], Awaitable[bool]], attempts: int, base_delay: float, what: str
+) -> bool:
+    """Call attempt until it returns True, at most attempts times.
+
+    The delay between tries starts at base_delay seconds and doubles. Returns
+    whether an attempt succeeded.
+    """
+    for number in range(1, attempts + 1):
+        if await attempt():
+            return True
+        if number < attempts:
+            delay = base_delay * 2 ** (number - 1)
+            logger.warning(f"{what} failed (attempt {number}/{attempts}), retrying in {delay:g}s")
+            await asyncio.sleep(delay)
+    return False
+
+
+def format_duration(seconds: float) -> str:
+    """Human-readable duration, e.g. "45 s", "3 min 20 s", "1 h 5 min"."""
+    seconds = max(0, round(seconds))
+    hours, rest = divmod(seconds, 3600)
+    minutes, secs = divmod(rest, 60)
+    if hours:
+        return f"{hours} h {minutes} min" if minutes else f"{hours} h"
+    if minutes:
+        return f"{minutes} min {secs} s" if secs else f"{minutes} min"
+    return f"{secs} s"
+
+
+def to_local(moment: datetime, tz: ZoneInfo) -> datetime:
+    """The moment in the user's TIMEZONE. Aware datetimes (e.g. UTC) are
+    converted, naive ones are taken as the host's local time."""
+    return moment.astimezone(tz)
+
+
+def in_hour_window(hour: int, start: int, end: int) -> bool:
+    """Whether hour lies in [start, end), for windows that may wrap past midnight (22–7)."""
+    if start <= end:
+        return start <= hour < end
+    return hour >= start or hour < end
+
+
+def html_to_text(text: str) -> str:
+    """Strip HTML tags and entities for sending a message without parse mode."""
+    return html.unescape(re.sub(r"<[^>]+>", "", text))
+
+
+PROXY_SCHEMES = ("http", "https", "socks4", "socks5", "socks5h")
+# Time zone of quiet hours, the daily summary and shown timestamps
+DEFAULT_TIMEZONE = "Europe/Berlin"
+# Shortest allowed time in seconds between two checks of a website, so a typo
+# in MONITOR_INTERVAL can't get the bot banned for hammering the sites
+MIN_MONITOR_INTERVAL = 10
+
+
+def parse_proxy_url(key: str, value) -> Optional[str]:
+    """Validate an optional proxy URL from config, e.g. "socks5://127.0.0.1:1080"."""
+    if not value:
+        return None
+    parsed = urlparse(str(value))
+    if parsed.scheme not in PROXY_SCHEMES or not parsed.hostname:
+        raise ValueError(
+            f"{key} must look like scheme://host:port with scheme one of "
+            f"{', '.join(PROXY_SCHEMES)}, got {value!r}"
+        )
+    return str(value)
+
+
+def parse_scraper_maps(key: str, value) -> Dict[str, Dict[str, str]]:
+    """Validate a {scraper name: {name: value}} setting such as SCRAPER_COOKIES."""
+    if not value:
+        return {}
+    if not isinstance(value, dict):
+        raise ValueError(f"{key} must map scraper names to objects")
+    maps = {}
+    for name, entries in value.items():
+        if not isinstance(entries, dict):
+            raise ValueError(f"{key}[{name}] must be an object of names to values")
+        maps[name] = {str(entry): str(entry_value) for entry, entry_value in entries.items()}
+    return maps
+
+
+def parse_chat_id(key: str, value) -> str:
+    """Validate a numeric Telegram chat ID from config and return it normalized."""
+    try:
+        return str(int(str(value).strip()))
+    except ValueError:
+        raise ValueError(f"{key} must be a numeric Telegram chat ID, got {value!r}")
+
+
+def parse_recipient(key: str, value) -> str:
+    """Validate a chat to send to: a numeric chat ID or the @username of a public
+    channel or group. Usernames are lowercased, Telegram ignores their case."""
+    text = str(value).strip()
+    if text.startswith("@"):
+        if not re.fullmatch(r"@[A-Za-z][A-Za-z0-9_]{3,31}", text):
+            raise ValueError(f"{key} must be a numeric chat ID or a valid @username, got {value!r}")
+        return text.lower()
+    try:
+        return parse_chat_id(key, text)
+    except ValueError:
+        raise ValueError(f"{key} must be a numeric chat ID or an @username, got {value!r}")
+
+
+def is_chat_in(chat, recipients: List[str]) -> bool:
+    """Whether a chat is one of the configured recipients, by ID or by @username."""
+    if str(chat.id) in recipients:
+        return True
+    username = getattr(chat, "username", None)
+    return bool(username) and f"@{username.lower()}" in recipients
+
+
+def read_secret(config: Dict, key: str):
+    """Return config[key], or the trimmed contents of the file named by KEY_FILE.
+
+    KEY_FILE may be set in config.json or as an environment variable (Docker and
+    Kubernetes secrets are mounted as files). It is only used when the direct
+    value is empty.
+    """
+    value = config.get(key)
+    if value:
+        return value
+    path = config.get(f"{key}_FILE") or os.environ.get(f"{key}_FILE")
+    if not path:
+        return value
+    try:
+        with open(path, "r") as f:
+            return f.read().strip()
+    except OSError as e:
+        raise ValueError(f"{key}_FILE could not be read: {e}")
+
+
+# Backends that NOTIFIERS may list
+NOTIFIER_NAMES = ("telegram", "discord")
+# Values of WBS_POLICY, what happens with flats that require a WBS
+WBS_POLICIES = ("exclude", "include", "separate_chat")
+
+
+def wbs_route(flat: FlatDetails, policy: str) -> Optional[str]:
+    """Where a flat is notified under a WBS_POLICY: "main" for the notification
+    chats, "wbs" for WBS_CHAT_ID, None if it isn't notified."""
+    if not flat.wbs_required:
+        return "main"
+    if policy == "exclude":
+        return None
+    return "wbs" if policy == "separate_chat" else "main"
+
+
+def is_authorized_user(user_id, authorized_user_ids: List[str]) -> bool:
+    """Whether a user may issue commands; everyone may if no user IDs are configured."""
+    if not authorized_user_ids:
+        return True
+    return user_id is not None and str(user_id) in authorized_user_ids
+
+
 class Config:
     def __init__(self, config_path: str = "config.json"):
         self.config_path = config_path
         self.bot_token: str = ""
         self.chat_id: str = ""
+        self.chat_ids: List[str] = []
         self.private_chat_id: str = ""
+        self.authorized_user_ids: List[str] = []
         self.monitor_interval: int = 60
+        self.accept_language: str = "de-DE,de;q=0.9"
+        self.user_agents: List[str] = []
+        self.high_traffic_markers: Optional[List[str]] = None
+        self.parse_failure_threshold: Optional[float] = None
+        self.criteria = FilterCriteria()
+        self.state_file: Optional[str] = None
+        self.database_url: Optional[str] = None
+        self.notification_log_path: Optional[str] = None
+        self.debug_save_html: bool = False
+        self.geocoding_url: str = DEFAULT_GEOCODING_URL
+        self.debug_dir: str = "debug_html"
+        self.error_notify_cooldown: int = 3600
+        self.batch_notifications: bool = False
+        self.batch_size: int = 10
+        self.min_batch_size: int = 1
+        self.max_hold_seconds: int = 600
+        self.allow_subscriptions: bool = False
+        self.metrics_port: Optional[int] = None
+        self.health_port: Optional[int] = None
+        self.scraper_intervals: Dict[str, int] = {}
+        self.scraper_urls: Dict[str, List[str]] = {}
+        self.scraper_retries: Dict[str, Dict[str, int]] = {}
+        self.scraper_headers: Dict[str, Dict[str, str]] = {}
+        self.scraper_cookies: Dict[str, Dict[str, str]] = {}
+        self.generic_scrapers: List[Dict] = []
+        self.enabled_scrapers: Optional[List[str]] = None
+        self.max_concurrent_scrapers: Optional[int] = None
+        self.inter_scraper_delay_ms: int = 0
+        self.send_interval_ms: int = 1000
+        self.list_limit: int = 5
+        self.timezone: ZoneInfo = ZoneInfo(DEFAULT_TIMEZONE)
+        self.dedup_ttl_days: Optional[float] = None
+        self.max_requests_per_minute: Optional[int] = None
+        self.telegram_mode: str = "polling"
+        self.telegram_timeout: int = 10
+        self.webhook_url: Optional[str] = None
+        self.webhook_listen: str = "0.0.0.0"
+        self.webhook_port: int = 8443
+        self.webhook_secret: Optional[str] = None
+        self.dry_run: bool = False
+        self.welcome_to_private_only: bool = True
+        self.wbs_policy: str = "exclude"
+        self.wbs_chat_id: str = ""
+        self.daily_summary_hour: Optional[int] = None
+        self.quiet_hours_start: Optional[int] = None
+        self.quiet_hours_end: Optional[int] = None
+        self.proxy_url: Optional[str] = None
+        self.max_pages: int = 3
+        self.notification_template: Optional[str] = None
+        self.message_footer: Optional[str] = None
+        self.telegram_proxy_url: Optional[str] = None
+        self.notifiers: List[str] = ["telegram"]
+        self.discord_webhook_url: Optional[str] = None
+        self.discord_admin_webhook_url: Optional[str] = None
         self.load_config()
 
     def load_config(self):
         try:
             with open(self.config_path, "r") as f:
                 config = json.load(f)
+            if not isinstance(config, dict):
+                raise RuntimeError(
+                    f"{self.config_path} must contain a JSON object with the settings, "
+                    f"found {type(config).__name__}"
+                )
 
-            self.bot_token = config["BOT_TOKEN"]
-            self.chat_id = config["CHAT_ID"]
-            self.private_chat_id = config["PRIVATE_CHAT_ID"]
+            self.bot_token = read_secret(config, "BOT_TOKEN")
+            if not self.bot_token:
+                raise KeyError("BOT_TOKEN")
+            # CHAT_ID may be a single id or a list; CHAT_IDS is accepted as an alias
+            chat_ids = config.get("CHAT_IDS") or read_secret(config, "CHAT_ID")
+            if not chat_ids:
+                raise KeyError("CHAT_ID")
+            if isinstance(chat_ids, str):
+                # A chat ID file may list several ids, comma or line separated
+                chat_ids = [chat_id for chat_id in re.split(r"[,\s]+", chat_ids) if chat_id]
+            elif not isinstance(chat_ids, list):
+                chat_ids = [chat_ids]
+            self.chat_ids = [parse_recipient("CHAT_ID", chat_id) for chat_id in chat_ids]
+            self.chat_id = self.chat_ids[0]
+            private_chat_id = read_secret(config, "PRIVATE_CHAT_ID")
+            if not private_chat_id:
+                raise KeyError("PRIVATE_CHAT_ID")
+            self.private_chat_id = parse_recipient("PRIVATE_CHAT_ID", private_chat_id)
+            # A single user ID may be given without a list, like CHAT_ID
+            user_ids = config.get("AUTHORIZED_USER_IDS") or []
+            if isinstance(user_ids, str):
+                user_ids = [user_id for user_id in re.split(r"[,\s]+", user_ids) if user_id]
+            elif not isinstance(user_ids, list):
+                user_ids = [user_ids]
+            self.authorized_user_ids = [
+                parse_chat_id("AUTHORIZED_USER_IDS", user_id) for user_id in user_ids
+            ]
             self.monitor_interval = int(config.get("MONITOR_INTERVAL", 60))
+            if self.monitor_interval < MIN_MONITOR_INTERVAL:
+                raise ValueError(
+                    f"MONITOR_INTERVAL must be at least {MIN_MONITOR_INTERVAL} seconds, "
+                    f"got {self.monitor_interval}"
+                )
+            self.scraper_intervals = {}
+            for name, seconds in config.get("SCRAPER_INTERVALS", {}).items():
+                if int(seconds) < MIN_MONITOR_INTERVAL:
+                    logger.warning(
+                        f"SCRAPER_INTERVALS[{name}] is below {MIN_MONITOR_INTERVAL} seconds, "
+                        f"using {MIN_MONITOR_INTERVAL}"
+                    )
+                self.scraper_intervals[name] = max(MIN_MONITOR_INTERVAL, int(seconds))
+            self.scraper_urls = {}
+            for name, urls in config.get("SCRAPER_URLS", {}).items():
+                urls = [urls] if isinstance(urls, str) else list(urls)
+                if not urls:
+                    raise ValueError(f"SCRAPER_URLS[{name}] must list at least one URL")
+                self.scraper_urls[name] = urls
+            self.scraper_retries = {}
+            for name, overrides in config.get("SCRAPER_RETRIES", {}).items():
+                unknown = set(overrides) - {"max_retries", "base_backoff", "max_backoff"}
+                if unknown:
+                    raise ValueError(
+                        f"SCRAPER_RETRIES[{name}]: unknown keys {', '.join(sorted(unknown))}"
+                    )
+                self.scraper_retries[name] = {
+                    key: int(value) for key, value in overrides.items()
+                }
+            self.scraper_headers = parse_scraper_maps("SCRAPER_HEADERS", config.get("SCRAPER_HEADERS"))
+            self.scraper_cookies = parse_scraper_maps("SCRAPER_COOKIES", config.get("SCRAPER_COOKIES"))
+            self.accept_language = config.get("ACCEPT_LANGUAGE", self.accept_language)
+            self.user_agents = list(config.get("USER_AGENTS", []))
+            if config.get("HIGH_TRAFFIC_MARKERS") is not None:
+                self.high_traffic_markers = [
+                    str(marker) for marker in config["HIGH_TRAFFIC_MARKERS"] if str(marker).strip()
+                ]
+            if config.get("PARSE_FAILURE_THRESHOLD") is not None:
+                self.parse_failure_threshold = float(config["PARSE_FAILURE_THRESHOLD"])
+            # Without WBS_POLICY, ALLOW_WBS decides between include and exclude
+            default_policy = "include" if config.get("ALLOW_WBS") else "exclude"
+            self.wbs_policy = str(config.get("WBS_POLICY", default_policy)).lower()
+            if self.wbs_policy not in WBS_POLICIES:
+                raise ValueError(
+                    f"WBS_POLICY must be one of {', '.join(WBS_POLICIES)}, got {config['WBS_POLICY']!r}"
+                )
+            if self.wbs_policy == "separate_chat":
+                if not config.get("WBS_CHAT_ID"):
+                    raise ValueError("WBS_CHAT_ID is required when WBS_POLICY is 'separate_chat'")
+                self.wbs_chat_id = parse_recipient("WBS_CHAT_ID", config["WBS_CHAT_ID"])
+            self.criteria = FilterCriteria(
+                min_rooms=float(config.get("MIN_ROOMS", 2.0)),
+                max_rooms=float(config["MAX_ROOMS"]) if config.get("MAX_ROOMS") is not None else None,
+                allow_wbs=self.wbs_policy != "exclude",
+                require_balcony=bool(config.get("REQUIRE_BALCONY", False)),
+                require_elevator=bool(config.get("REQUIRE_ELEVATOR", False)),
+                max_rent=float(config["MAX_RENT"]) if config.get("MAX_RENT") is not None else None,
+                min_area=float(config["MIN_AREA"]) if config.get("MIN_AREA") is not None else None,
+                districts=list(config.get("DISTRICTS", [])),
+                exclude_keywords=list(config.get("EXCLUDE_KEYWORDS", [])),
+                include_keywords=list(config.get("INCLUDE_KEYWORDS", [])),
+                min_floor=int(config["MIN_FLOOR"]) if config.get("MIN_FLOOR") is not None else None,
+                max_floor=int(config["MAX_FLOOR"]) if config.get("MAX_FLOOR") is not None else None,
+                max_floor_without_elevator=(
+                    int(config["MAX_FLOOR_WITHOUT_ELEVATOR"])
+                    if config.get("MAX_FLOOR_WITHOUT_ELEVATOR") is not None
+                    else None
+                ),
+                center_lat=float(config["CENTER_LAT"]) if config.get("CENTER_LAT") is not None else None,
+                center_lon=float(config["CENTER_LON"]) if config.get("CENTER_LON") is not None else None,
+                max_distance_km=(
+                    float(config["MAX_DISTANCE_KM"])
+                    if config.get("MAX_DISTANCE_KM") is not None
+                    else None
+                ),
+                available_before=(
+                    parse_cutoff_date("AVAILABLE_BEFORE", config["AVAILABLE_BEFORE"])
+                    if config.get("AVAILABLE_BEFORE")
+                    else None
+                ),
+            )
+            if self.criteria.max_distance_km is not None and not self.criteria.uses_distance():
+                raise ValueError("MAX_DISTANCE_KM requires CENTER_LAT and CENTER_LON")
+            self.geocoding_url = config.get("GEOCODING_URL") or self.geocoding_url
+            self.state_file = config.get("STATE_FILE")
+            self.database_url = config.get("DATABASE_URL") or None
+            self.notification_log_path = config.get("NOTIFICATION_LOG_PATH") or None
+            self.debug_save_html = bool(config.get("DEBUG_SAVE_HTML", False))
+            self.debug_dir = config.get("DEBUG_DIR") or self.debug_dir
+            if self.database_url and not self.database_url.startswith("sqlite:///"):
+                raise ValueError(
+                    f"DATABASE_URL must look like sqlite:///path/to/flats.db, got {self.database_url!r}"
+                )
+            self.error_notify_cooldown = int(
+                config.get("ERROR_NOTIFY_COOLDOWN", self.error_notify_cooldown)
+            )
+            self.batch_notifications = bool(config.get("BATCH_NOTIFICATIONS", False))
+            self.batch_size = max(1, int(config.get("BATCH_SIZE", self.batch_size)))
+            self.min_batch_size = max(1, int(config.get("MIN_BATCH_SIZE", self.min_batch_size)))
+            self.max_hold_seconds = max(
+                0, int(config.get("MAX_HOLD_SECONDS", self.max_hold_seconds))
+            )
+            self.allow_subscriptions = bool(config.get("ALLOW_SUBSCRIPTIONS", False))
+            metrics_port = config.get("METRICS_PORT")
+            self.metrics_port = int(metrics_port) if metrics_port else None
+            health_port = config.get("HEALTH_PORT")
+            self.health_port = int(health_port) if health_port else None
+            self.max_pages = max(1, int(config.get("MAX_PAGES", self.max_pages)))
+            if config.get("ENABLED_SCRAPERS") is not None:
+                self.enabled_scrapers = list(config["ENABLED_SCRAPERS"])
+            self.generic_scrapers = list(config.get("GENERIC_SCRAPERS", []))
+            for index, scraper_config in enumerate(self.generic_scrapers):
+                missing = [
+                    key
+                    for key in ("name", "url", "item_selector")
+                    if not scraper_config.get(key)
+                ]
+                if missing:
+                    raise ValueError(
+                        f"GENERIC_SCRAPERS entry {index} is missing {', '.join(missing)}"
+                    )
+            max_concurrent = config.get("MAX_CONCURRENT_SCRAPERS")
+            self.max_concurrent_scrapers = int(max_concurrent) if max_concurrent else None
+            self.inter_scraper_delay_ms = int(config.get("INTER_SCRAPER_DELAY_MS", 0))
+            self.send_interval_ms = max(
+                0, int(config.get("SEND_INTERVAL_MS", self.send_interval_ms))
+            )
+            self.list_limit = int(config.get("LIST_LIMIT", self.list_limit))
+            if not 1 <= self.list_limit <= MAX_LIST_LIMIT:
+                raise ValueError(f"LIST_LIMIT must be between 1 and {MAX_LIST_LIMIT}")
+            dedup_ttl = config.get("DEDUP_TTL_DAYS")
+            self.dedup_ttl_days = float(dedup_ttl) if dedup_ttl else None
+            if self.dedup_ttl_days is not None and self.dedup_ttl_days < 0:
+                raise ValueError("DEDUP_TTL_DAYS must not be negative")
+            max_requests = config.get("MAX_REQUESTS_PER_MINUTE")
+            self.max_requests_per_minute = int(max_requests) if max_requests else None
+            if self.max_requests_per_minute is not None and self.max_requests_per_minute < 1:
+                raise ValueError("MAX_REQUESTS_PER_MINUTE must be at least 1")
+            self.telegram_mode = str(config.get("TELEGRAM_MODE", "polling")).lower()
+            if self.telegram_mode not in ("polling", "webhook"):
+                raise ValueError("TELEGRAM_MODE must be 'polling' or 'webhook'")
+            self.telegram_timeout = int(config.get("TELEGRAM_TIMEOUT", self.telegram_timeout))
+            if self.telegram_timeout < 0:
+                raise ValueError("TELEGRAM_TIMEOUT must not be negative")
+            self.webhook_url = config.get("WEBHOOK_URL")
+            if self.telegram_mode == "webhook" and not self.webhook_url:
+                raise ValueError("WEBHOOK_URL is required when TELEGRAM_MODE is 'webhook'")
+            self.webhook_listen = config.get("WEBHOOK_LISTEN", self.webhook_listen)
+            self.webhook_port = int(config.get("WEBHOOK_PORT", self.webhook_port))
+            self.webhook_secret = config.get("WEBHOOK_SECRET")
+            self.dry_run = bool(config.get("DRY_RUN", False))
+            notifiers = config.get("NOTIFIERS", self.notifiers)
+            if isinstance(notifiers, str):
+                notifiers = [notifiers]
+            self.notifiers = [str(name).lower() for name in notifiers]
+            unknown = set(self.notifiers) - set(NOTIFIER_NAMES)
+            if unknown or not self.notifiers:
+                raise ValueError(
+                    f"NOTIFIERS must list one or more of {', '.join(NOTIFIER_NAMES)}, "
+                    f"got {notifiers!r}"
+                )
+            self.discord_webhook_url = config.get("DISCORD_WEBHOOK_URL") or None
+            self.discord_admin_webhook_url = config.get("DISCORD_ADMIN_WEBHOOK_URL") or None
+            if "discord" in self.notifiers and not self.discord_webhook_url:
+                raise ValueError("DISCORD_WEBHOOK_URL is required when NOTIFIERS includes 'discord'")
+            self.welcome_to_private_only = bool(config.get("WELCOME_TO_PRIVATE_ONLY", True))
+            self.notification_template = config.get("NOTIFICATION_TEMPLATE")
+            if self.notification_template:
+                try:
+                    self.notification_template.format_map(_BlankDict())
+                except (ValueError, IndexError) as e:
+                    raise ValueError(f"Invalid NOTIFICATION_TEMPLATE: {e}")
+            footer = config.get("MESSAGE_FOOTER")
+            if footer is not None and not isinstance(footer, str):
+                raise ValueError("MESSAGE_FOOTER must be a string")
+            self.message_footer = (footer or "").strip() or None
+            self.proxy_url = parse_proxy_url("PROXY_URL", config.get("PROXY_URL"))
+            if self.proxy_url and self.proxy_url.startswith("socks"):
+                # Only imported when the first request is made, fail at startup instead
+                try:
+                    import aiohttp_socks  # noqa: F401
+                except ImportError:
+                    raise ValueError(
+                        "PROXY_URL: SOCKS proxies need the aiohttp-socks package "
+                        "(pip install aiohttp-socks)"
+                    )
+            self.telegram_proxy_url = parse_proxy_url(
+                "TELEGRAM_PROXY_URL", config.get("TELEGRAM_PROXY_URL")
+            )
+            summary_hour = config.get("DAILY_SUMMARY_HOUR")
+            if summary_hour is not None:
+                self.daily_summary_hour = int(summary_hour)
+                if not 0 <= self.daily_summary_hour <= 23:
+                    raise ValueError("DAILY_SUMMARY_HOUR must be between 0 and 23")
+            timezone_name = config.get("TIMEZONE", DEFAULT_TIMEZONE)
+            try:
+                self.timezone = ZoneInfo(str(timezone_name))
+            except (ZoneInfoNotFoundError, ValueError):
+                raise ValueError(
+                    f"Unknown TIMEZONE '{timezone_name}', use an IANA name like 'Europe/Berlin'"
+                )
+            quiet_start = config.get("QUIET_HOURS_START")
+            quiet_end = config.get("QUIET_HOURS_END")
+            if (quiet_start is None) != (quiet_end is None):
+                raise ValueError("QUIET_HOURS_START and QUIET_HOURS_END must be set together")
+            if quiet_start is not None:
+                self.quiet_hours_start = int(quiet_start)
+                self.quiet_hours_end = int(quiet_end)
+                if not (0 <= self.quiet_hours_start <= 23 and 0 <= self.quiet_hours_end <= 23):
+                    raise ValueError("QUIET_HOURS_START and QUIET_HOURS_END must be between 0 and 23")
 
             logger.info(
                 f"Loaded configuration with monitor interval: {self.monitor_interval} seconds"
             )
         except FileNotFoundError:
-            raise RuntimeError("config.json not found!")
-        except json.JSONDecodeError:
-            raise RuntimeError("Invalid JSON in config.json!")
+            raise RuntimeError(f"{self.config_path} not found!")
+        except json.JSONDecodeError as e:
+            # Point at the mistake, e.g. a trailing comma or a missing quote
+            raise RuntimeError(
+                f"Invalid JSON in {self.config_path} at line {e.lineno}, column {e.colno}: {e.msg}"
+            )
+        except RuntimeError:
+            raise
         except KeyError as e:
             raise RuntimeError(f"Missing required configuration: {e}")
         except Exception as e:
             raise RuntimeError(f"Error loading configuration: {e}")
 
 
+# Config fields that /reload cannot apply, because they are used once at startup
+RESTART_REQUIRED_FIELDS = {
+    "bot_token",
+    "chat_id",
+    "chat_ids",
+    "private_chat_id",
+    "state_file",
+    "database_url",
+    "accept_language",
+    "user_agents",
+    "proxy_url",
+    "telegram_proxy_url",
+    "max_requests_per_minute",
+    "scraper_urls",
+    "scraper_retries",
+    "generic_scrapers",
+    "enabled_scrapers",
+    "max_concurrent_scrapers",
+    "metrics_port",
+    "health_port",
+    "telegram_mode",
+    "telegram_timeout",
+    "webhook_url",
+    "webhook_listen",
+    "webhook_port",
+    "webhook_secret",
+    "notifiers",
+    "discord_webhook_url",
+    "discord_admin_webhook_url",
+}
+
+
+def config_changes(old: Config, new: Config) -> List[str]:
+    """Names of the Config fields whose values differ between old and new."""
+    changed = []
+    for name, value in vars(new).items():
+        old_value = getattr(old, name, None)
+        if name == "criteria":
+            if value.to_dict() != old_value.to_dict():
+                changed.append(name)
+        elif value != old_value:
+            changed.append(name)
+    return changed
+
+
+# Telegram rejects messages longer than this many characters
+MAX_MESSAGE_LENGTH = 4096
+# Tries and first retry delay in seconds for the welcome message
+WELCOME_ATTEMPTS = 4
+WELCOME_RETRY_DELAY = 5
+# Number of notified flats remembered for /recent
+RECENT_FLATS_LIMIT = 50
+# Most flats /list sends at once, one message each
+MAX_LIST_LIMIT = 20
+
+
+def clamp_list_limit(value, default: int) -> int:
+    """Number of flats for /list from a command argument, default without one.
+
+    Clamped to 1..MAX_LIST_LIMIT. Raises ValueError if it isn't a whole number.
+    """
+    if value is None:
+        return default
+    return min(MAX_LIST_LIMIT, max(1, int(value)))
+
+# Detail fields shown first, in this order, by /details; others follow alphabetically
+DETAIL_ORDER = [
+    StandardFields.ROOMS,
+    StandardFields.AREA,
+    StandardFields.RENT_WARM,
+    StandardFields.RENT_TOTAL,
+    StandardFields.RENT_COLD,
+    StandardFields.RENT_ADDITIONAL,
+    StandardFields.RENT_HEATING,
+    StandardFields.ADDRESS,
+    StandardFields.DISTRICT,
+    StandardFields.FLOOR,
+    StandardFields.AVAILABLE_FROM,
+    StandardFields.PROVIDER,
+    StandardFields.OBJECT_ID,
+]
+
+# Public commands in /help order: name -> (usage, summary, explanation for /help <name>)
+COMMAND_HELP: Dict[str, Tuple[str, str, str]] = {
+    "list": (
+        "/list [scraper] [n]",
+        "Show latest flats",
+        "Shows the flats currently listed on all websites, or only on the given "
+        "scraper, e.g. <code>/list gesobau</code>. Shows LIST_LIMIT flats (5 by "
+        f"default), or n of them, up to {MAX_LIST_LIMIT}: <code>/list 10</code>.",
+    ),
+    "status": (
+        "/status",
+        "Check website status",
+        "Shows whether each website could be fetched in the last check, how long "
+        "it took, when a website in backoff is retried and how reliable it was "
+        "recently (the share of successful fetches, weighted towards the latest).",
+    ),
+    "nextcheck": (
+        "/nextcheck",
+        "Time until the next check",
+        "Shows when the websites are checked next. With SCRAPER_INTERVALS, "
+        "the next check of each website is listed.",
+    ),
+    "scan": (
+        "/scan",
+        "Check all websites now",
+        "Runs a check of every website right away, without waiting for the "
+        "interval, and notifies about new matching flats as usual. Websites "
+        "in backoff are skipped.",
+    ),
+    "sources": (
+        "/sources",
+        "List the monitored websites",
+        "Lists the active scrapers with a link to the page each of them polls.",
+    ),
+    "test": (
+        "/test [scraper]",
+        "Test all scrapers",
+        "Fetches every website now, or only the given scraper (e.g. "
+        "<code>/test degewo</code>), and sends the first flat found.",
+    ),
+    "clear": (
+        "/clear",
+        "Clear cache",
+        "Forgets all seen flats, so every current listing counts as new again.",
+    ),
+    "filter": (
+        "/filter [name value]",
+        "Show or change filters",
+        "Without arguments shows the active filters. <code>/filter max_rent 900</code> "
+        "changes one, <code>/filter max_rent none</code> clears it.",
+    ),
+    "recent": (
+        "/recent [n]",
+        "Show the last notified flats",
+        f"Lists the last n notified flats, 10 by default and up to {RECENT_FLATS_LIMIT}.",
+    ),
+    "details": (
+        "/details <id>",
+        "Show everything about one flat",
+        "Shows all known details of a flat. The ID is shown below each flat in /list.",
+    ),
+    "save": (
+        "/save <id>",
+        "Bookmark a flat",
+        "Adds a flat from the current listings to this chat's favorites. The ID is "
+        "shown below each flat in /list.",
+    ),
+    "favorites": (
+        "/favorites",
+        "Show bookmarked flats",
+        "Lists the flats saved in this chat with /save, even after they are gone "
+        "from the website.",
+    ),
+    "export": (
+        "/export",
+        "Download current flats as JSON",
+        "Sends the current flats of all websites as a JSON file.",
+    ),
+    "subscribe": (
+        "/subscribe",
+        "Get new flats in this chat",
+        "Sends new flat notifications to this chat as well. Stop them with /unsubscribe.",
+    ),
+    "whoami": (
+        "/whoami",
+        "Show this chat's ID",
+        "Replies with the ID of this chat and your user ID, for CHAT_ID, "
+        "PRIVATE_CHAT_ID and AUTHORIZED_USER_IDS. Works in any chat.",
+    ),
+    "help": (
+        "/help [command]",
+        "Show this help",
+        "Shows all commands, or explains one, e.g. <code>/help filter</code>.",
+    ),
+}
+
+
+def telegram_request(config: Config) -> Optional[HTTPXRequest]:
+    """HTTP client for Telegram API calls, going through TELEGRAM_PROXY_URL if set."""
+    if not config.telegram_proxy_url:
+        return None
+    return HTTPXRequest(proxy=config.telegram_proxy_url)
+
+
+class _BlankDict(dict):
+    """Placeholder values for str.format_map, unknown placeholders render empty."""
+
+    def __missing__(self, key):
+        return ""
+
+
 class MessageFormatter:
+    def __init__(self, template: Optional[str] = None, footer: Optional[str] = None):
+        # NOTIFICATION_TEMPLATE, replaces the default flat message layout
+        self.template = template
+        # MESSAGE_FOOTER, plain text appended to every flat message
+        self.footer = footer
+
+    @staticmethod
+    def template_values(flat: FlatDetails) -> Dict[str, str]:
+        """HTML-escaped values for NOTIFICATION_TEMPLATE placeholders."""
+        values = {key: str(value) for key, value in flat.details.items() if value}
+        values.update(
+            id=flat.id,
+            title=flat.title,
+            link=flat.canonical_link() or "",
+            source=flat.source,
+            wbs="WBS required" if flat.wbs_required else "",
+            warmmiete=values.get(StandardFields.RENT_WARM)
+            or values.get(StandardFields.RENT_TOTAL, ""),
+            kaltmiete=values.get(StandardFields.RENT_COLD, ""),
+        )
+        values.setdefault(StandardFields.DISTRICT, flat.detected_district() or "")
+        return {key: html.escape(value) for key, value in values.items()}
+
+    @staticmethod
+    def render_template(template: str, flat: FlatDetails) -> str:
+        return template.format_map(_BlankDict(MessageFormatter.template_values(flat)))
+
+    def format_flat_message(self, flat: FlatDetails) -> str:
+        if self.template:
+            message = self.render_template(self.template, flat)
+        else:
+            message = self.format_default_flat_message(flat)
+        return self.add_footer(message)
+
+    def add_footer(self, message: str) -> str:
+        """Append the footer, unless the message would get too long for Telegram."""
+        if not self.footer:
+            return message
+        body = message.rstrip("\n")
+        footer = f"\n\n<i>{html.escape(self.footer)}</i>"
+        if len(body) + len(footer) > MAX_MESSAGE_LENGTH:
+            logger.warning("Flat message too long for MESSAGE_FOOTER, sending it without")
+            return message
+        return f"{body}{footer}\n"
+
     @staticmethod
     def _is_empty_value(value: str) -> bool:
         """Check if a value is empty or contains only units without data."""
@@ -93,12 +812,14 @@ class MessageFormatter:
         return False
 
     @staticmethod
-    def format_flat_message(flat: FlatDetails) -> str:
+    def format_default_flat_message(flat: FlatDetails) -> str:
+        # Scraped texts are escaped, a stray & or < would make Telegram reject the message
+        esc = html.escape
         # Title line with link - bold and prominent using <b> tag (Telegram doesn't support <h1>)
         if flat.link:
-            message = f"<b>🔗 <a href='{flat.link}'>{flat.title}</a></b>\n\n"
+            message = f"<b>🔗 <a href='{flat.canonical_link()}'>{esc(flat.title)}</a></b>\n\n"
         else:
-            message = f"<b>🔗 {flat.title}</b>\n\n"
+            message = f"<b>🔗 {esc(flat.title)}</b>\n\n"
 
         if not flat.details:
             return message
@@ -113,9 +834,9 @@ class MessageFormatter:
             match = re.search(r'(\d+(?:\.\d+)?)', cleaned)
             return float(match.group(1)) if match else None
 
-        # Address + District combined line
+        # Address + District combined line, the Bezirk detected from the address if not given
         address = flat.details.get(StandardFields.ADDRESS)
-        district = flat.details.get(StandardFields.DISTRICT)
+        district = flat.details.get(StandardFields.DISTRICT) or flat.detected_district()
 
         if address and not MessageFormatter._is_empty_value(str(address)):
             from urllib.parse import quote
@@ -125,9 +846,9 @@ class MessageFormatter:
                 display_address = address
             encoded_address = quote(address)
             maps_link = f"https://www.google.com/maps/search/?api=1&query={encoded_address}"
-            message += f"📍 <a href='{maps_link}' >{display_address}</a>\n"
+            message += f"📍 <a href='{maps_link}' >{esc(display_address)}</a>\n"
         elif district and not MessageFormatter._is_empty_value(str(district)):
-            message += f"📍 {district}\n"
+            message += f"📍 {esc(district)}\n"
 
         # Rooms + Area + Price per m² combined line
         rooms = flat.details.get(StandardFields.ROOMS)
@@ -161,7 +882,7 @@ class MessageFormatter:
                 size_parts.append(f"{price_per_sqm:.1f} €/m²")
 
         if size_parts:
-            message += f"📐 {' • '.join(size_parts)}\n"
+            message += f"📐 {esc(' • '.join(size_parts))}\n"
 
         # Rent display - show the most important rent information
         rent_cold = flat.details.get(StandardFields.RENT_COLD)
@@ -181,21 +902,21 @@ class MessageFormatter:
             rent_parts.append(f"{rent_cold} cold")
 
         if rent_parts:
-            message += f"💰 {' • '.join(rent_parts)}\n"
+            message += f"💰 {esc(' • '.join(rent_parts))}\n"
         elif rent_additional and not MessageFormatter._is_empty_value(str(rent_additional)):
-            message += f"💰 {rent_additional} utilities\n"
+            message += f"💰 {esc(rent_additional)} utilities\n"
 
         # Available from
         available = flat.details.get(StandardFields.AVAILABLE_FROM)
         if available and not MessageFormatter._is_empty_value(str(available)):
-            message += f"📅 {available}\n"
+            message += f"📅 {esc(available)}\n"
 
         # Features
         features = flat.details.get(StandardFields.FEATURES)
         if features and not MessageFormatter._is_empty_value(str(features)):
             features_list = [f.strip() for f in features.replace(' · ', ', ').split(',')]
             if features_list:
-                message += f"✨ {' • '.join(features_list)}\n"
+                message += f"✨ {esc(' • '.join(features_list))}\n"
 
         # Provider + Object ID combined
         provider = flat.details.get(StandardFields.PROVIDER)
@@ -208,8 +929,119 @@ class MessageFormatter:
             provider_parts.append(f"ID {object_id}")
 
         if provider_parts:
-            message += f"🏢 {' • '.join(provider_parts)}\n"
+            message += f"🏢 {esc(' • '.join(provider_parts))}\n"
+
+        return message
+
+    @staticmethod
+    def batch_messages(
+        messages: List[str], max_per_batch: int, max_length: int = MAX_MESSAGE_LENGTH
+    ) -> List[str]:
+        """Join formatted flat messages into as few messages as possible.
+
+        A batch holds at most max_per_batch flats and is split early when it
+        would exceed Telegram's message length. Flats are never cut in half.
+        """
+        separator = "\n"
+        batches = []
+        current: List[str] = []
+        length = 0
+        for message in messages:
+            added = len(message) + (len(separator) if current else 0)
+            if current and (len(current) >= max_per_batch or length + added > max_length):
+                batches.append(separator.join(current))
+                current, length = [], 0
+                added = len(message)
+            current.append(message)
+            length += added
+        if current:
+            batches.append(separator.join(current))
+        return batches
+
+    @staticmethod
+    def ordered_details(flat: FlatDetails) -> List[Tuple[str, str]]:
+        """Detail items with the DETAIL_ORDER fields first, then the rest by name."""
+        rank = {key: index for index, key in enumerate(DETAIL_ORDER)}
+        return sorted(
+            flat.details.items(),
+            key=lambda item: (rank.get(item[0], len(DETAIL_ORDER)), item[0]),
+        )
+
+    @staticmethod
+    def format_flat_details(flat: FlatDetails) -> str:
+        """All known information about a flat, for /details."""
+        if flat.link:
+            message = f"<b>🔗 <a href='{flat.canonical_link()}'>{html.escape(flat.title)}</a></b>\n\n"
+        else:
+            message = f"<b>🔗 {html.escape(flat.title)}</b>\n\n"
+
+        for key, value in MessageFormatter.ordered_details(flat):
+            if key == StandardFields.FEATURES or MessageFormatter._is_empty_value(str(value)):
+                continue
+            label = key.replace("_", " ").capitalize()
+            message += f"<b>{label}:</b> {html.escape(str(value))}\n"
 
+        if StandardFields.DISTRICT not in flat.details and flat.detected_district():
+            message += f"<b>District:</b> {flat.detected_district()} (detected)\n"
+
+        features = flat.details.get(StandardFields.FEATURES)
+        if features and not MessageFormatter._is_empty_value(str(features)):
+            message += "\n<b>Features:</b>\n"
+            for feature in features.replace(" · ", ", ").split(","):
+                if feature.strip():
+                    message += f"• {html.escape(feature.strip())}\n"
+
+        message += f"\n<b>WBS:</b> {'required' if flat.wbs_required else 'not required'}\n"
+        message += f"<b>Source:</b> {flat.source}\n"
+        if flat.listed_at:
+            message += f"<b>Listed:</b> {flat.listed_at:%d.%m.%Y %H:%M}\n"
+        message += f"<b>ID:</b> <code>{html.escape(flat.id)}</code>\n"
+        if flat.link:
+            message += f"\n{html.escape(flat.canonical_link())}"
+        return message
+
+    @staticmethod
+    def format_price_change_message(
+        flat: FlatDetails, old_rent: float, new_rent: float
+    ) -> str:
+        arrow = "📈" if new_rent > old_rent else "📉"
+        title = html.escape(flat.title)
+        if flat.link:
+            title = f"<a href='{flat.canonical_link()}'>{title}</a>"
+        return (
+            f"{arrow} <b>Price changed</b>\n\n"
+            f"{title}\n"
+            f"💰 {old_rent:g} € → <b>{new_rent:g} €</b> ({new_rent - old_rent:+g} €)\n"
+            f"🏢 {html.escape(flat.source)}"
+        )
+
+    @staticmethod
+    def format_recent_message(
+        recent: List[Tuple[datetime, FlatDetails]], tz: ZoneInfo
+    ) -> str:
+        message = f"🕒 <b>Last {len(recent)} notified flats</b>\n\n"
+        for notified_at, flat in recent:
+            title = html.escape(flat.title)
+            if flat.link:
+                title = f"<a href='{flat.canonical_link()}'>{title}</a>"
+            message += f"{to_local(notified_at, tz):%d.%m. %H:%M} – {title} ({flat.source})\n"
+        return message
+
+    @staticmethod
+    def format_daily_summary(
+        counts: Dict[str, int], top_matches: List[FlatDetails]
+    ) -> str:
+        message = "📊 <b>Daily Summary</b>\n\n<b>New flats in the last 24 hours:</b>\n"
+        for source, count in sorted(counts.items()):
+            message += f"• {html.escape(source)}: {count}\n"
+        if top_matches:
+            message += "\n<b>Matching your filters:</b>\n"
+            for flat in top_matches:
+                title = html.escape(flat.title)
+                if flat.link:
+                    message += f"• <a href='{flat.canonical_link()}'>{title}</a>\n"
+                else:
+                    message += f"• {title}\n"
         return message
 
     @staticmethod
@@ -217,17 +1049,106 @@ class MessageFormatter:
         return (
             "🏠 <b>Berlin Flat Monitor</b>\n\n"
             "<b>Commands:</b>\n"
-            "`/list [scraper]` – Show latest flats\n"
-            "`/status` – Check website status\n"
-            "`/test` – Test all scrapers\n"
-            "`/clear` – Clear cache\n"
-            "`/help` – Show this help\n\n"
-            "<b>Providers:</b> InBerlinWohnen • Degewo • Gesobau • Gewobag • Stadt und Land"
+            + "".join(
+                f"`{html.escape(usage)}` – {summary}\n"
+                for usage, summary, _ in COMMAND_HELP.values()
+            )
+            + "\n<b>Providers:</b> InBerlinWohnen • Degewo • Gesobau • Gewobag • Stadt und Land"
         )
 
     @staticmethod
-    def format_status_message(website_statuses: Dict[str, str]) -> str:
+    def format_sources_message(scrapers: List) -> str:
+        message = f"🔎 <b>{len(scrapers)} monitored websites</b>\n\n"
+        for scraper in scrapers:
+            message += f"• <a href='{html.escape(scraper.urls[0])}'>{html.escape(scraper.name)}</a>"
+            for number, url in enumerate(scraper.urls[1:], start=2):
+                message += f" · <a href='{html.escape(url)}'>{number}</a>"
+            message += "\n"
+        return message
+
+    @staticmethod
+    def format_command_help(command: str) -> Optional[str]:
+        """Focused help for one command, None if it is unknown."""
+        entry = COMMAND_HELP.get(command.lower().lstrip("/"))
+        if entry is None:
+            return None
+        usage, summary, explanation = entry
+        return f"<b>{html.escape(usage)}</b> – {summary}\n\n{explanation}"
+
+    @staticmethod
+    def format_filter_message(criteria: FilterCriteria) -> str:
+        def show(value):
+            if value is None or value == []:
+                return "off"
+            if isinstance(value, list):
+                return ", ".join(value)
+            if isinstance(value, float):
+                return f"{value:g}"
+            if isinstance(value, date):
+                return f"{value:%d.%m.%Y}"
+            return str(value).lower()
+
+        message = "🎯 <b>Filters</b>\n\n"
+        message += f"min_rooms: <i>{show(criteria.min_rooms)}</i>\n"
+        message += f"max_rooms: <i>{show(criteria.max_rooms)}</i>\n"
+        message += f"min_area: <i>{show(criteria.min_area)}</i>\n"
+        message += f"min_floor: <i>{show(criteria.min_floor)}</i>\n"
+        message += f"max_floor: <i>{show(criteria.max_floor)}</i>\n"
+        message += (
+            f"max_floor_without_elevator: <i>{show(criteria.max_floor_without_elevator)}</i>\n"
+        )
+        message += f"max_rent: <i>{show(criteria.max_rent)}</i>\n"
+        message += f"available_before: <i>{show(criteria.available_before)}</i>\n"
+        message += f"max_distance_km: <i>{show(criteria.max_distance_km)}</i>"
+        if criteria.center_lat is not None and criteria.center_lon is not None:
+            message += f" from <i>{criteria.center_lat:g}, {criteria.center_lon:g}</i>"
+        message += "\n"
+        message += f"allow_wbs: <i>{show(criteria.allow_wbs)}</i>\n"
+        message += f"require_balcony: <i>{show(criteria.require_balcony)}</i>\n"
+        message += f"require_elevator: <i>{show(criteria.require_elevator)}</i>\n"
+        message += f"districts: <i>{show(criteria.districts)}</i>\n"
+        message += f"include_keywords: <i>{show(criteria.include_keywords)}</i>\n"
+        message += f"exclude_keywords: <i>{show(criteria.exclude_keywords)}</i>\n\n"
+        message += "Change with <code>/filter name value</code>, clear with <code>/filter name none</code>"
+        return message
+
+    @staticmethod
+    def format_whoami_message(chat, user) -> str:
+        """Plain text reply to /whoami with the IDs needed in config.json."""
+        lines = [f"Chat ID: {chat.id}"]
+        if getattr(chat, "username", None):
+            lines.append(f"Chat username: @{chat.username}")
+        if user is not None:
+            lines.append(f"User ID: {user.id}")
+        return "\n".join(lines)
+
+    @staticmethod
+    def format_next_check_message(
+        remaining: Optional[timedelta], per_scraper: Dict[str, timedelta]
+    ) -> str:
+        if remaining is None:
+            return "⏱ The first check is running right now."
+        if remaining <= timedelta(0):
+            message = "⏱ The next check is due now."
+        else:
+            message = f"⏱ Next check in <b>{format_duration(remaining.total_seconds())}</b>."
+        if len(set(per_scraper.values())) > 1:
+            message += "\n"
+            for name, until in sorted(per_scraper.items(), key=lambda item: item[1]):
+                message += f"\n• {html.escape(name)}: {format_duration(until.total_seconds())}"
+        return message
+
+    @staticmethod
+    def format_status_message(
+        website_statuses: Dict[str, str],
+        durations_ms: Optional[Dict[str, int]] = None,
+        backoff_remaining: Optional[Dict[str, Optional[timedelta]]] = None,
+        reliability: Optional[Dict[str, Optional[int]]] = None,
+    ) -> str:
         message = "🌐 <b>Website Status</b>\n\n"
+        durations_ms = durations_ms or {}
+        backoff_remaining = backoff_remaining or {}
+        reliability = reliability or {}
 
         for website, status in website_statuses.items():
             status_lower = status.lower()
@@ -244,90 +1165,456 @@ class MessageFormatter:
             else:
                 icon = "✅"
 
-            message += f"{icon} {website}: <i>{status}</i>\n"
+            message += f"{icon} {website}: <i>{status}</i>"
+            if durations_ms.get(website) is not None:
+                message += f" ({durations_ms[website]} ms)"
+            if reliability.get(website) is not None:
+                message += f" • {reliability[website]}% reliable"
+            message += "\n"
+            remaining = backoff_remaining.get(website)
+            if remaining is not None:
+                message += f"    ⏸ retry in {int(remaining.total_seconds()) + 1}s\n"
 
         return message
 
 
+class TelegramNotifier(Notifier):
+    """Sends to the notification chats and subscribers, operator messages to PRIVATE_CHAT_ID.
+
+    Under WBS_POLICY separate_chat, WBS flats go to WBS_CHAT_ID and the subscribers instead.
+    """
+
+    name = "telegram"
+
+    def __init__(self, monitor: "FlatMonitor"):
+        self.monitor = monitor
+
+    async def broadcast(self, text: str, silent: bool = False) -> List[Exception]:
+        return await self.monitor._broadcast_telegram(
+            text,
+            parse_mode="HTML",
+            disable_web_page_preview=True,
+            disable_notification=silent,
+        )
+
+    async def broadcast_wbs(self, text: str, silent: bool = False) -> List[Exception]:
+        return await self.monitor._broadcast_telegram(
+            text,
+            chats=self.monitor._wbs_chats(),
+            parse_mode="HTML",
+            disable_web_page_preview=True,
+            disable_notification=silent,
+        )
+
+    async def send_admin(self, text: str, silent: bool = False):
+        await self.monitor._send_message(
+            self.monitor.private_chat_id,
+            text,
+            parse_mode="HTML",
+            disable_notification=silent,
+        )
+
+
 class FlatMonitor:
-    def __init__(self, config: Config):
+    def __init__(self, config: Config, scrapers: Optional[List] = None):
         self.config = config
-        self.bot = Bot(token=config.bot_token)
-        self.chat_id = config.chat_id
+        self.bot = Bot(token=config.bot_token, request=telegram_request(config))
+        self.chat_ids = list(config.chat_ids)
         self.private_chat_id = config.private_chat_id
-        self.current_flats: List[FlatDetails] = []
+        # All notifications are sent through this queue, paced by SEND_INTERVAL_MS
+        self.send_queue = SendQueue(self.bot.send_message, config.send_interval_ms / 1000)
+        # Backends flats and operator messages are sent through (NOTIFIERS)
+        self.notifiers: List[Notifier] = self._create_notifiers(config)
+        self.state = StateStore(config.state_file)
+        # Resolves addresses for the distance filter, results are cached in the state
+        self.geocoder = Geocoder(config.geocoding_url, self.state)
+        # Seen flats and, with DATABASE_URL, flat snapshots and notification history
+        self.store = create_store(config.database_url)
+        self.store.load()
+        # Chats registered at runtime via /subscribe, notified in addition to chat_ids
+        self.subscribers: List[str] = list(self.state.get("subscribers", []))
+        # Filters changed via /filter take precedence over the ones in config.json
+        saved_filters = self.state.get("filters")
+        self.criteria = (
+            FilterCriteria.from_dict(saved_filters) if saved_filters else config.criteria
+        )
+        self.current_flats: List[FlatDetails] = self._load_current_flats()
+        # Flats bookmarked via /save, per chat
+        self.favorites: Dict[str, List[FlatDetails]] = {
+            chat_id: [FlatDetails.from_dict(data) for data in flats]
+            for chat_id, flats in self.state.get("favorites", {}).items()
+        }
         self.application: Optional[Application] = None
-        self.formatter = MessageFormatter()
+        self.formatter = MessageFormatter(config.notification_template, config.message_footer)
+        self.metrics = Metrics()
+        # End of the last monitoring cycle that completed without an error
+        self.last_successful_cycle: Optional[datetime] = None
+
+        if config.dry_run:
+            logger.info("Dry run: notifications are logged instead of sent")
+
+        # Apply request defaults to the shared HTTP session before any scraper runs
+        configure_session(
+            accept_language=config.accept_language,
+            user_agents=config.user_agents,
+            proxy_url=config.proxy_url,
+            max_requests_per_minute=config.max_requests_per_minute,
+        )
+
+        # Initialize scrapers and their status; test scripts may pass their own
+        self.scrapers = scrapers if scrapers is not None else self._create_scrapers(config)
+        self._apply_scraper_settings(config)
+        for scraper in self.scrapers:
+            for name, overrides in config.scraper_retries.items():
+                if scraper.matches_name(name):
+                    scraper.configure_retries(**overrides)
+            for name, urls in config.scraper_urls.items():
+                if not scraper.matches_name(name):
+                    continue
+                if scraper.supports_urls:
+                    scraper.set_urls(urls)
+                else:
+                    logger.warning(f"SCRAPER_URLS: {scraper.name} doesn't use search URLs, ignoring")
+
+        # Last parsed total rent per flat ID, to detect price changes
+        self.known_rents: Dict[str, float] = {
+            flat.id: flat.total_rent()
+            for flat in self.current_flats
+            if flat.total_rent() is not None
+        }
+
+        # Flats users were notified about (in UTC), newest last, for /recent
+        self.recent_flats: Deque[Tuple[datetime, FlatDetails]] = deque(
+            maxlen=RECENT_FLATS_LIMIT
+        )
+
+        # Matching flats held back until MIN_BATCH_SIZE is reached or they are
+        # MAX_HOLD_SECONDS old, with the time the oldest one was added
+        self.pending_flats: List[FlatDetails] = []
+        self.pending_since: Optional[datetime] = None
+
+        # New flats of the last 24 hours for the daily summary: (seen at, flat, matched)
+        self.recent_activity: List[Tuple[datetime, FlatDetails, bool]] = []
+
+        # Limits how many scrapers fetch at the same time (MAX_CONCURRENT_SCRAPERS)
+        self.scraper_semaphore = (
+            asyncio.Semaphore(config.max_concurrent_scrapers)
+            if config.max_concurrent_scrapers
+            else None
+        )
+
+        # When each scraper is due for its next fetch (SCRAPER_INTERVALS)
+        self.next_due: Dict[str, datetime] = {}
+
+        # Last error notification per source: (message, time sent)
+        self.error_notifications: Dict[str, Tuple[str, datetime]] = {}
+
+        # Whether the empty scrapers of the first cycle were reported
+        self.self_test_done = False
+
+        # Held during a check, so /scan and the monitoring loop never overlap
+        self.scan_lock = asyncio.Lock()
 
-        # Initialize scrapers and their status
-        self.scrapers = [
-            InBerlinWohnenScraper("https://inberlinwohnen.de/wohnungsfinder/"),
-            # DegewoScraper("https://www.degewo.de/immosuche"),
-            # GesobauScraper("https://www.gesobau.de/mieten/wohnungssuche/"),
-            # GewobagScraper("https://www.gewobag.de/fuer-mieter-und-mietinteressenten/mietangebote/?objekttyp%5B%5D=wohnung&gesamtmiete_von=&gesamtmiete_bis=&gesamtflaeche_von=&gesamtflaeche_bis=&zimmer_von=&zimmer_bis=&sort-by="),
-            # StadtUndLandScraper("https://stadtundland.de/wohnungssuche")
-        ]
         # Initialize status for all scrapers
         self.website_statuses = {
-            scraper.__class__.__name__: "Not checked yet" for scraper in self.scrapers
+            scraper.name: "Not checked yet" for scraper in self.scrapers
         }
 
+    def _create_notifiers(self, config: Config) -> List[Notifier]:
+        notifiers: List[Notifier] = []
+        for name in config.notifiers:
+            if name == "telegram":
+                notifiers.append(TelegramNotifier(self))
+            elif name == "discord":
+                notifiers.append(
+                    DiscordNotifier(
+                        config.discord_webhook_url,
+                        config.discord_admin_webhook_url,
+                        dry_run=config.dry_run,
+                    )
+                )
+        return notifiers
+
+    def _apply_scraper_settings(self, config: Config):
+        """Apply the scraper settings that may change at runtime via /reload."""
+        for scraper in self.scrapers:
+            scraper.parse_failure_threshold = config.parse_failure_threshold
+            scraper.max_pages = config.max_pages
+            scraper.debug_dir = config.debug_dir if config.debug_save_html else None
+            if config.high_traffic_markers is not None:
+                scraper.high_traffic_markers = list(config.high_traffic_markers)
+            scraper.extra_headers, scraper.cookies = {}, {}
+            for name, headers in config.scraper_headers.items():
+                if scraper.matches_name(name):
+                    scraper.extra_headers = dict(headers)
+            for name, cookies in config.scraper_cookies.items():
+                if scraper.matches_name(name):
+                    scraper.cookies = dict(cookies)
+
+    def reload_config(self) -> Tuple[List[str], List[str]]:
+        """Re-read the config file and apply it.
+
+        Returns the changed fields that were applied and the changed fields
+        that only take effect after a restart; those keep their old value.
+        Raises RuntimeError if the new config is invalid, leaving the
+        current one in place.
+        """
+        new_config = Config(self.config.config_path)
+        changed = config_changes(self.config, new_config)
+        applied = [name for name in changed if name not in RESTART_REQUIRED_FIELDS]
+        needs_restart = [name for name in changed if name in RESTART_REQUIRED_FIELDS]
+        for name in needs_restart:
+            setattr(new_config, name, getattr(self.config, name))
+
+        self.config = new_config
+        # Filters changed via /filter still take precedence
+        if "criteria" in applied and not self.state.get("filters"):
+            self.criteria = new_config.criteria
+        if "notification_template" in applied or "message_footer" in applied:
+            self.formatter = MessageFormatter(
+                new_config.notification_template, new_config.message_footer
+            )
+        self._apply_scraper_settings(new_config)
+        self.send_queue.interval_seconds = new_config.send_interval_ms / 1000
+        self.geocoder.url = new_config.geocoding_url
+
+        logger.info(
+            f"Reloaded configuration, applied: {', '.join(applied) or 'none'}, "
+            f"restart required: {', '.join(needs_restart) or 'none'}"
+        )
+        return applied, needs_restart
+
+    @staticmethod
+    def _create_scrapers(config: Config) -> List:
+        """Built-in and generic scrapers, limited to ENABLED_SCRAPERS if set."""
+        scrapers = [
+            InBerlinWohnenScraper("https://inberlinwohnen.de/wohnungsfinder/"),
+            DegewoScraper("https://www.degewo.de/immosuche"),
+            GesobauScraper("https://www.gesobau.de/mieten/wohnungssuche/"),
+            GewobagScraper("https://www.gewobag.de/fuer-mieter-und-mietinteressenten/mietangebote/?objekttyp%5B%5D=wohnung&gesamtmiete_von=&gesamtmiete_bis=&gesamtflaeche_von=&gesamtflaeche_bis=&zimmer_von=&zimmer_bis=&sort-by="),
+            StadtUndLandScraper("https://stadtundland.de/wohnungssuche"),
+        ]
+        scrapers.extend(
+            GenericScraper.from_config(scraper_config)
+            for scraper_config in config.generic_scrapers
+        )
+
+        if config.enabled_scrapers is None:
+            return scrapers
+
+        for name in config.enabled_scrapers:
+            if not any(scraper.matches_name(name) for scraper in scrapers):
+                logger.warning(f"ENABLED_SCRAPERS: unknown scraper {name}")
+        return [
+            scraper
+            for scraper in scrapers
+            if any(scraper.matches_name(name) for name in config.enabled_scrapers)
+        ]
+
+    def _load_current_flats(self) -> List[FlatDetails]:
+        """Restore the flats cache saved on the last shutdown, if any."""
+        flats = []
+        for data in self.state.get("current_flats", []):
+            try:
+                flats.append(FlatDetails.from_dict(data))
+            except (TypeError, ValueError) as e:
+                logger.warning(f"Skipping invalid cached flat: {e}")
+        if flats:
+            logger.info(f"Restored {len(flats)} flats from the state file")
+        return flats
+
+    def save_current_flats(self):
+        """Persist the flats cache so the first check after a restart only reports changes."""
+        self.state.set("current_flats", [flat.to_dict() for flat in self.current_flats])
+
+    async def _is_authorized(self, update: Update, chats: Optional[List[str]] = None) -> bool:
+        """Check if a command comes from one of the notification chats (or the
+        given chats) and, with AUTHORIZED_USER_IDS, from one of those users.
+
+        Commands from other chats are ignored silently, other users of an
+        allowed chat get a short denial.
+        """
+        if not is_chat_in(update.effective_chat, self.chat_ids if chats is None else chats):
+            return False
+        user = update.effective_user
+        if is_authorized_user(user.id if user else None, self.config.authorized_user_ids):
+            return True
+        logger.info(f"Ignoring command from unauthorized user {user.id if user else None}")
+        try:
+            await update.message.reply_text("Sorry, you are not allowed to use this bot's commands.")
+        except TelegramError as e:
+            logger.error(f"Failed to send authorization denial: {e}")
+        return False
+
+    async def _reply_each(self, update: Update, messages: List[str], what: str, **kwargs) -> int:
+        """Reply with each message in turn. A failed one is logged and the rest
+        are still sent. Returns the number of messages sent."""
+        sent = 0
+        for message in messages:
+            try:
+                await update.message.reply_text(text=message, **kwargs)
+                sent += 1
+            except TelegramError as e:
+                logger.error(f"Failed to send {what}: {e}")
+        return sent
+
+    async def _send_message(self, chat_id: str, text: str, **kwargs):
+        """Send a message to a single chat, following a migration to a supergroup.
+
+        In DRY_RUN mode the message is only logged.
+        """
+        if self.config.dry_run:
+            logger.info(f"[dry run] Message to chat {chat_id}:\n{text}")
+            return
+        try:
+            await self._send_with_fallback(chat_id, text, **kwargs)
+        except ChatMigrated as e:
+            new_chat_id = str(e.new_chat_id)
+            self.chat_ids = [new_chat_id if c == chat_id else c for c in self.chat_ids]
+            if chat_id in self.subscribers:
+                self.subscribers = [
+                    new_chat_id if c == chat_id else c for c in self.subscribers
+                ]
+                self.state.set("subscribers", self.subscribers)
+            logger.info(f"Chat {chat_id} migrated, updated chat ID to {new_chat_id}")
+            await self._send_with_fallback(new_chat_id, text, **kwargs)
+
+    async def _send_with_fallback(self, chat_id: str, text: str, **kwargs):
+        """Send a message, retrying as plain text if Telegram rejects its markup."""
+        try:
+            await self._queue_send(chat_id, text, **kwargs)
+        except BadRequest as e:
+            if not kwargs.get("parse_mode") or not is_markup_error(e):
+                raise
+            logger.warning(f"Message markup rejected ({e}), resending as plain text")
+            kwargs.pop("parse_mode")
+            await self._queue_send(chat_id, html_to_text(text), **kwargs)
+
+    async def _queue_send(self, chat_id: str, text: str, **kwargs):
+        """Send a message through the send queue, which also handles Telegram's RetryAfter."""
+        await self.send_queue.send(chat_id=chat_id, text=text, **kwargs)
+
+    def _notification_chats(self) -> List[str]:
+        """Configured chats followed by subscribers, without duplicates."""
+        chats = list(self.chat_ids)
+        chats.extend(c for c in self.subscribers if c not in chats)
+        return chats
+
+    def _wbs_chats(self) -> List[str]:
+        """WBS_CHAT_ID followed by subscribers, the recipients of WBS flats under separate_chat."""
+        chats = [self.config.wbs_chat_id]
+        chats.extend(c for c in self.subscribers if c not in chats)
+        return chats
+
+    async def _broadcast(self, text: str, silent: bool = False, wbs: bool = False) -> List[Exception]:
+        """Send an HTML message through every notifier and return the errors of failed sends.
+
+        With wbs, it's a WBS flat for the separate WBS targets (WBS_POLICY separate_chat).
+        """
+        errors = []
+        for notifier in self.notifiers:
+            send = notifier.broadcast_wbs if wbs else notifier.broadcast
+            errors.extend(await send(text, silent=silent))
+        return errors
+
+    def _notification_target_count(self) -> int:
+        """Number of sends a broadcast makes: one per Telegram chat, one per other backend."""
+        return sum(
+            len(self._notification_chats()) if isinstance(notifier, TelegramNotifier) else 1
+            for notifier in self.notifiers
+        )
+
+    async def _send_admin(self, text: str, silent: bool = False) -> List[Exception]:
+        """Send an HTML message to the operator through every notifier, logging failures."""
+        errors = []
+        for notifier in self.notifiers:
+            try:
+                await notifier.send_admin(text, silent=silent)
+            except Exception as e:
+                logger.error(f"Failed to send operator message via {notifier.name}: {e}")
+                errors.append(e)
+        return errors
+
+    async def _broadcast_telegram(
+        self, text: str, chats: Optional[List[str]] = None, **kwargs
+    ) -> List[TelegramError]:
+        """Send a message to chats (default: every notification chat) and return the
+        errors of failed sends."""
+        errors = []
+        for chat_id in chats if chats is not None else self._notification_chats():
+            try:
+                await self._send_message(chat_id, text, **kwargs)
+            except TelegramError as e:
+                logger.error(f"Failed to send message to chat {chat_id}: {e}")
+                errors.append(e)
+        return errors
+
     async def send_welcome(self):
-        # Check if current time is within allowed hours (8 AM - 8 PM)
-        current_hour = datetime.now().hour
-        is_quiet_hours = not (8 <= current_hour < 20)
+        is_quiet_hours = self._is_quiet_hours()
 
+        if self.config.quiet_hours_start is None:
+            quiet_line = "🔕 Silent notifications: 8 PM - 8 AM"
+        else:
+            quiet_line = (
+                f"🔕 Quiet hours: {self.config.quiet_hours_start}:00 - "
+                f"{self.config.quiet_hours_end}:00"
+            )
         welcome_text = (
             "🏠 <b>Berlin Flat Monitor Started</b>\n\n"
             f"Monitoring {len(self.scrapers)} provider(s) every {self.config.monitor_interval}s\n"
-            "🎯 2+ rooms • No WBS required\n"
-            "🔕 Silent notifications: 8 PM - 8 AM\n\n"
+            f"🎯 {self.criteria.describe()}\n"
+            f"{quiet_line}\n\n"
         )
-        try:
-            await self.bot.send_message(
-                chat_id=self.chat_id,
-                text=welcome_text,
-                parse_mode="HTML",
-                disable_notification=is_quiet_hours
-            )
-            logger.info(f"Welcome message sent silently to chat {self.chat_id}")
-        except TelegramError as e:
-            error_msg = f"Failed to send welcome message: {str(e)}"
-            logger.error(error_msg)
-            await self.send_error_notification(error_msg)
-
-            if isinstance(e, ChatMigrated):
-                self.chat_id = str(e.new_chat_id)
-                logger.info(f"Updated chat ID to {self.chat_id}")
-                try:
-                    await self.bot.send_message(
-                        chat_id=self.chat_id,
-                        text=welcome_text,
-                        parse_mode="HTML",
-                        disable_notification=is_quiet_hours
-                    )
-                    logger.info(f"Welcome message sent silently to new chat {self.chat_id}")
-                    return
-                except TelegramError as retry_error:
-                    error_msg = f"Failed to send welcome message to new chat: {str(retry_error)}"
-                    logger.error(error_msg)
-                    await self.send_error_notification(error_msg)
+        errors: List[Exception] = []
+        # Restarts shouldn't ping the whole group, unless WELCOME_TO_PRIVATE_ONLY is off
+        private_only = self.config.welcome_to_private_only
+
+        async def attempt() -> bool:
+            nonlocal errors
+            if private_only:
+                errors = await self._send_admin(welcome_text, silent=is_quiet_hours)
+                return len(errors) < len(self.notifiers)
+            errors = await self._broadcast(welcome_text, silent=is_quiet_hours)
+            # Retry only if nothing was delivered, so no chat gets it twice
+            return len(errors) < self._notification_target_count()
+
+        delivered = await with_retries(
+            attempt, WELCOME_ATTEMPTS, WELCOME_RETRY_DELAY, "Welcome message"
+        )
+        if not errors:
+            if private_only:
+                logger.info("Welcome message sent to the private chat")
+            else:
+                logger.info(
+                    f"Welcome message sent to {len(self._notification_chats())} chat(s)"
+                )
+            return
 
-            exit()
+        error_msg = f"Failed to send welcome message: {str(errors[0])}"
+        logger.error(error_msg)
+        await self.send_error_notification(error_msg)
+        if not delivered:
+            # Monitoring goes on; Telegram may be reachable again by the first new flat
+            logger.error("Welcome message could not be delivered anywhere, monitoring anyway")
 
     async def handle_help_command(
         self, update: Update, context: ContextTypes.DEFAULT_TYPE
     ):
-        if str(update.effective_chat.id) != self.chat_id:
+        if not await self._is_authorized(update):
             return
 
-        # Check if current time is within allowed hours (8 AM - 8 PM)
-        current_hour = datetime.now().hour
-        is_quiet_hours = not (8 <= current_hour < 20)
+        is_quiet_hours = self._is_quiet_hours()
+
+        text = self.formatter.format_help_message()
+        if context.args:
+            command = context.args[0]
+            text = self.formatter.format_command_help(command) or (
+                f"Unknown command: {html.escape(command)}\n\n{text}"
+            )
 
         try:
             await update.message.reply_text(
-                text=self.formatter.format_help_message(),
+                text=text,
                 parse_mode="HTML",
                 disable_notification=is_quiet_hours,
             )
@@ -338,18 +1625,21 @@ class FlatMonitor:
     async def handle_status_command(
         self, update: Update, context: ContextTypes.DEFAULT_TYPE
     ):
-        if str(update.effective_chat.id) != self.chat_id:
+        if not await self._is_authorized(update):
             return
 
-        # Check if current time is within allowed hours (8 AM - 8 PM)
-        current_hour = datetime.now().hour
-        is_quiet_hours = not (8 <= current_hour < 20)
+        is_quiet_hours = self._is_quiet_hours()
 
         try:
             # Update statuses before showing
             await self.fetch_all_flats()
 
-            status_message = self.formatter.format_status_message(self.website_statuses)
+            status_message = self.formatter.format_status_message(
+                self.website_statuses,
+                {scraper.name: scraper.last_duration_ms for scraper in self.scrapers},
+                {scraper.name: scraper.backoff_remaining() for scraper in self.scrapers},
+                {scraper.name: scraper.reliability() for scraper in self.scrapers},
+            )
             await update.message.reply_text(
                 text=status_message,
                 parse_mode="HTML",
@@ -360,78 +1650,229 @@ class FlatMonitor:
             logger.error(f"Failed to send status message: {e}")
             await self.send_error_notification(f"Failed to send status message: {e}")
 
-    async def fetch_all_flats(self) -> List[FlatDetails]:
-        """Fetch flats from all sources."""
-        all_flats = []
-        for scraper in self.scrapers:
+    async def handle_nextcheck_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /nextcheck command to show the time until the next scan."""
+        if not await self._is_authorized(update):
+            return
+
+        per_scraper = self.time_until_next_checks()
+        remaining = min(per_scraper.values()) if per_scraper else None
+        try:
+            await update.message.reply_text(
+                self.formatter.format_next_check_message(remaining, per_scraper),
+                parse_mode="HTML",
+                disable_notification=self._is_quiet_hours(),
+            )
+        except TelegramError as e:
+            logger.error(f"Failed to send next check time: {e}")
+
+    async def handle_scan_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /scan command to check all websites right away."""
+        if not await self._is_authorized(update):
+            return
+
+        try:
+            if self.scan_lock.locked():
+                await update.message.reply_text("⏳ A check is already running, please wait.")
+                return
+            await update.message.reply_text("🔍 Checking all websites...")
             try:
-                flats = await scraper.fetch_flats()
-                all_flats.extend(flats)
-                self.website_statuses[scraper.__class__.__name__] = "Available"
-            except WebsiteUnavailableError as e:
-                logger.error(f"Website unavailable: {e}")
-                self.website_statuses[scraper.__class__.__name__] = str(e)
-            except HighTrafficError as e:
-                logger.error(f"High traffic: {e}")
-                self.website_statuses[scraper.__class__.__name__] = str(e)
-            except asyncio.TimeoutError as e:
-                logger.error(f"Timeout error: {e}")
-                self.website_statuses[scraper.__class__.__name__] = (
-                    "Timeout - Website not responding"
-                )
+                flats = await self.check_for_new_flats(force=True)
             except Exception as e:
-                logger.error(f"Unexpected error: {e}")
-                self.website_statuses[scraper.__class__.__name__] = f"Error: {str(e)}"
-        return all_flats
+                logger.error(f"Manual scan failed: {e}")
+                await update.message.reply_text(f"❌ Scan failed: {e}")
+                return
+            if flats:
+                text = f"✅ Scan done, new matching flats: {len(flats)}"
+            else:
+                text = "✅ Scan done, no new matching flats."
+            await update.message.reply_text(text, disable_notification=self._is_quiet_hours())
+            logger.info(f"Manual scan sent {len(flats)} flats")
+        except TelegramError as e:
+            logger.error(f"Failed to send scan reply: {e}")
+
+    async def fetch_all_flats(self, scrapers: Optional[List] = None) -> List[FlatDetails]:
+        """Fetch flats from all sources (or the given ones) concurrently.
+
+        Fetches are started INTER_SCRAPER_DELAY_MS apart and at most
+        MAX_CONCURRENT_SCRAPERS run at the same time.
+        """
+        if scrapers is None:
+            scrapers = self.scrapers
+        tasks = []
+        for index, scraper in enumerate(scrapers):
+            if index and self.config.inter_scraper_delay_ms:
+                await asyncio.sleep(self.config.inter_scraper_delay_ms / 1000)
+            tasks.append(asyncio.create_task(self._fetch_limited(scraper)))
+        results = await asyncio.gather(*tasks)
+        self.metrics.update_backoff(self.scrapers)
+        return [flat for flats in results for flat in flats]
+
+    async def _fetch_limited(self, scraper) -> List[FlatDetails]:
+        if self.scraper_semaphore is None:
+            return await self._fetch_from_scraper(scraper)
+        async with self.scraper_semaphore:
+            return await self._fetch_from_scraper(scraper)
+
+    async def _fetch_from_scraper(self, scraper) -> List[FlatDetails]:
+        """Fetch flats from one source and update its status. Never raises."""
+        started = time.monotonic()
+        try:
+            flats = await scraper.fetch_all()
+        except BackoffActiveError as e:
+            # Skipped because of an earlier error, which was already reported
+            logger.info(f"{scraper.name}: {e}")
+            self.website_statuses[scraper.name] = str(e)
+            # No request was made, keep the duration of the last one
+            return []
+        except WebsiteUnavailableError as e:
+            logger.error(f"Website unavailable: {e}")
+            self.website_statuses[scraper.name] = str(e)
+        except HighTrafficError as e:
+            logger.error(f"High traffic: {e}")
+            self.website_statuses[scraper.name] = str(e)
+        except ParsingError as e:
+            logger.error(f"Parsing error: {e}")
+            self.website_statuses[scraper.name] = f"Parsing error: {str(e)}"
+        except asyncio.TimeoutError as e:
+            logger.error(f"Timeout error: {e}")
+            self.website_statuses[scraper.name] = "Timeout - Website not responding"
+        except Exception as e:
+            logger.error(f"Unexpected error: {e}")
+            self.website_statuses[scraper.name] = f"Error: {str(e)}"
+        else:
+            scraper.last_duration_ms = round((time.monotonic() - started) * 1000)
+            scraper.record_result(True)
+            self.website_statuses[scraper.name] = "Available"
+            self.metrics.record_fetch(scraper.name, len(flats))
+            if not flats and scraper.last_snapshot_path:
+                logger.warning(
+                    f"{scraper.name}: no flats found, page saved to {scraper.last_snapshot_path}"
+                )
+            await self._notify_recovered(scraper.name)
+            return flats
+
+        scraper.last_duration_ms = round((time.monotonic() - started) * 1000)
+        scraper.record_result(False)
+        self.metrics.record_error(scraper.name)
+        await self._notify_error(
+            scraper.name, f"{scraper.name}: {self.website_statuses[scraper.name]}"
+        )
+        return []
+
+    async def _notify_error(self, key: str, message: str):
+        """Send an error notification unless the same error was reported recently.
+
+        An error for the same key is only repeated if the message changed or
+        ERROR_NOTIFY_COOLDOWN has elapsed since the last notification.
+        """
+        now = datetime.now()
+        last = self.error_notifications.get(key)
+        if last is not None:
+            last_message, last_sent = last
+            cooldown = timedelta(seconds=self.config.error_notify_cooldown)
+            if last_message == message and now - last_sent < cooldown:
+                logger.info(f"Suppressing repeated error notification for {key}")
+                return
+
+        self.error_notifications[key] = (message, now)
+        await self.send_error_notification(message)
+
+    async def _notify_recovered(self, key: str):
+        """Send a single recovery message if errors were reported for this key."""
+        if self.error_notifications.pop(key, None) is None:
+            return
+        if not await self._send_admin(f"✅ <b>{key} recovered</b>"):
+            logger.info(f"Recovery notification sent for {key}")
 
     async def send_update(self, new_flats: List[FlatDetails]):
         if not new_flats:
             return
 
-        # Check if current time is within allowed hours (8 AM - 8 PM)
-        current_hour = datetime.now().hour
-        is_quiet_hours = not (8 <= current_hour < 20)
+        is_quiet_hours = self._is_quiet_hours()
 
         if is_quiet_hours:
-            logger.info(f"Quiet hours ({current_hour}:00) - sending {len(new_flats)} flats silently")
+            logger.info(
+                f"Quiet hours ({self.local_now().hour}:00) - sending {len(new_flats)} flats silently"
+            )
 
-        try:
-            for flat in new_flats:
-                message = self.formatter.format_flat_message(flat)
-                await self.bot.send_message(
-                    chat_id=self.chat_id,
-                    text=message,
-                    parse_mode="HTML",
-                    disable_web_page_preview=True,
-                    disable_notification=is_quiet_hours,
-                )
-        except TelegramError as e:
-            logger.error(f"Failed to send update: {e}")
+        routes = self.route_flats(new_flats)
+        for route, flats in routes.items():
+            if not flats:
+                continue
+            messages = [self.formatter.format_flat_message(flat) for flat in flats]
+            if self.config.batch_notifications:
+                messages = self.formatter.batch_messages(messages, self.config.batch_size)
+                logger.info(f"Sending {len(flats)} flats in {len(messages)} message(s)")
+            for message in messages:
+                await self._broadcast(message, silent=is_quiet_hours, wbs=route == "wbs")
+
+        # Flats excluded by WBS_POLICY were not sent and don't count as notified
+        notified = routes["main"] + routes["wbs"]
+        self.metrics.record_notifications(len(notified))
+        now = datetime.now(timezone.utc)
+        self.recent_flats.extend((now, flat) for flat in notified)
+        self._append_notification_log(notified)
+
+    def route_flats(self, flats: List[FlatDetails]) -> Dict[str, List[FlatDetails]]:
+        """Split flats by wbs_route into "main" and "wbs"; excluded flats are left out."""
+        policy = self.config.wbs_policy
+        if policy == "exclude" and self.criteria.allow_wbs:
+            # WBS flats were allowed at runtime with /filter allow_wbs
+            policy = "include"
+        routes: Dict[str, List[FlatDetails]] = {"main": [], "wbs": []}
+        for flat in flats:
+            route = wbs_route(flat, policy)
+            if route is None:
+                logger.info(f"Not sending WBS flat {flat.id}, excluded by WBS_POLICY")
+            else:
+                routes[route].append(flat)
+        return routes
 
+    def _append_notification_log(self, flats: List[FlatDetails]):
+        """Append one JSON line per notified flat to NOTIFICATION_LOG_PATH, if set."""
+        path = self.config.notification_log_path
+        if not path:
+            return
+        notified_at = datetime.now(timezone.utc).isoformat()
+        try:
+            with open(path, "a", encoding="utf-8") as f:
+                for flat in flats:
+                    entry = {"notified_at": notified_at, **flat.to_dict()}
+                    f.write(json.dumps(entry, ensure_ascii=False) + "\n")
+        except OSError as e:
+            logger.error(f"Failed to write notification log {path}: {e}")
 
     async def handle_list_command(
         self, update: Update, context: ContextTypes.DEFAULT_TYPE
     ):
         logger.info(f"Received command: {update.message.text}")
         logger.info(f"Chat ID: {update.effective_chat.id}")
-        logger.info(f"Expected Chat ID: {self.chat_id}")
+        logger.info(f"Expected Chat IDs: {', '.join(self.chat_ids)}")
 
-        if str(update.effective_chat.id) != self.chat_id:
+        if not await self._is_authorized(update):
             logger.info("Message not from target chat, ignoring")
             return
 
         logger.info("Processing list command")
         logger.info(f"Current flats in cache: {len(self.current_flats)}")
 
-        # Check if current time is within allowed hours (8 AM - 8 PM)
-        current_hour = datetime.now().hour
-        is_quiet_hours = not (8 <= current_hour < 20)
+        is_quiet_hours = self._is_quiet_hours()
 
-        # Get the scraper name from the command if provided
+        # Optional scraper name and number of flats, in any order
         scraper_name = None
-        if context.args and len(context.args) > 0:
-            scraper_name = context.args[0].strip()
-            logger.info(f"Filtering by scraper: {scraper_name}")
+        count_arg = None
+        for arg in context.args or []:
+            if arg.strip().lstrip("-").isdigit():
+                count_arg = arg.strip()
+            else:
+                scraper_name = arg.strip()
+                logger.info(f"Filtering by scraper: {scraper_name}")
+        limit = clamp_list_limit(count_arg, self.config.list_limit)
 
         # Use cached flats
         flats = self.current_flats
@@ -464,37 +1905,19 @@ class FlatMonitor:
                     return
 
             # Apply WBS and room filters (same as monitoring loop)
-            def get_room_count(flat):
-                # Use standardized field name
-                if StandardFields.ROOMS in flat.details:
-                    try:
-                        room_str = flat.details[StandardFields.ROOMS].lower()
-                        import re
-                        match = re.search(r"\d+(?:[.,]\d+)?", room_str)
-                        if match:
-                            return float(match.group().replace(",", "."))
-                    except (ValueError, AttributeError):
-                        pass
-                return 0
-
-            # Filter for 2+ rooms and no WBS requirement
-            filtered_flats = [
-                flat
-                for flat in flats
-                if (get_room_count(flat) == 0 or get_room_count(flat) >= 2)
-                and not flat.wbs_required
-            ]
+            filtered_flats = [flat for flat in flats if flat.meets_criteria(self.criteria)]
 
             total_flats = len(flats)
             filtered_count = len(filtered_flats)
             logger.info(f"Total flats found: {total_flats}, after filters: {filtered_count}")
-            flats = filtered_flats[:5]  # Limit to 5 flats
+            flats = filtered_flats[:limit]
 
             # Add a header message
             if not flats:
-                header = f"Found {total_flats} flats total, but none match filters (2+ rooms, no WBS)"
+                criteria = self.criteria.describe()
+                header = f"Found {total_flats} flats total, but none match filters ({criteria})"
                 if scraper_name:
-                    header = f"Found {total_flats} flats from {scraper_name}, but none match filters (2+ rooms, no WBS)"
+                    header = f"Found {total_flats} flats from {scraper_name}, but none match filters ({criteria})"
                 await update.message.reply_text(header, disable_notification=is_quiet_hours)
                 return
 
@@ -502,185 +1925,448 @@ class FlatMonitor:
             if scraper_name:
                 header = f"Found {total_flats} flats from {scraper_name} ({filtered_count} after filters, showing {len(flats)})"
             await update.message.reply_text(header, disable_notification=is_quiet_hours)
-
-            for flat in flats:
-                message = self.formatter.format_flat_message(flat)
-                await update.message.reply_text(
-                    text=message,
-                    parse_mode="HTML",
-                    disable_web_page_preview=True,
-                    disable_notification=is_quiet_hours,
-                )
-            logger.info(f"Sent {len(flats)} flats")
         except TelegramError as e:
             logger.error(f"Failed to send list: {e}")
+            return
+
+        messages = [
+            self.formatter.format_flat_message(flat)
+            + f"\n<code>/details {html.escape(flat.id)}</code>"
+            for flat in flats
+        ]
+        sent = await self._reply_each(
+            update,
+            messages,
+            "flat in list",
+            parse_mode="HTML",
+            disable_web_page_preview=True,
+            disable_notification=is_quiet_hours,
+        )
+        logger.info(f"Sent {sent} of {len(flats)} flats")
 
     async def send_error_notification(self, error_message: str):
-        # Check if current time is within allowed hours (8 AM - 8 PM)
-        current_hour = datetime.now().hour
-        is_quiet_hours = not (8 <= current_hour < 20)
+        is_quiet_hours = self._is_quiet_hours()
 
-        try:
-            await self.bot.send_message(
-                chat_id=self.private_chat_id,
-                text=f"⚠️ <b>Error in Flat Monitor</b>\n\n{error_message}",
-                parse_mode="HTML",
-                disable_notification=is_quiet_hours,
+        errors = await self._send_admin(
+            f"⚠️ <b>Error in Flat Monitor</b>\n\n{error_message}", silent=is_quiet_hours
+        )
+        if not errors:
+            logger.info("Error notification sent")
+
+    def scraper_interval(self, scraper) -> int:
+        """Seconds between fetches for a scraper, from SCRAPER_INTERVALS or MONITOR_INTERVAL."""
+        for name, seconds in self.config.scraper_intervals.items():
+            if scraper.matches_name(name):
+                return seconds
+        return self.config.monitor_interval
+
+    def due_scrapers(self, now: Optional[datetime] = None) -> List:
+        """Scrapers whose interval has elapsed since their last fetch."""
+        now = now or datetime.now()
+        return [
+            scraper
+            for scraper in self.scrapers
+            if self.next_due.get(scraper.name, now) <= now
+        ]
+
+    def time_until_next_checks(
+        self, now: Optional[datetime] = None
+    ) -> Dict[str, timedelta]:
+        """Time until each scraper is due again, empty before the first check."""
+        now = now or datetime.now()
+        return {
+            name: max(timedelta(0), due - now) for name, due in self.next_due.items()
+        }
+
+    def _seconds_until_next_due(self) -> float:
+        if not self.next_due:
+            return self.config.monitor_interval
+        next_time = min(self.next_due.values())
+        return max(1.0, (next_time - datetime.now()).total_seconds())
+
+    async def check_for_new_flats(self, force: bool = False) -> List[FlatDetails]:
+        """Fetch flats from due scrapers (all with force), notify about new
+        matching ones and return them. Waits for a check already running."""
+        async with self.scan_lock:
+            return await self._check_for_new_flats(force)
+
+    async def _check_for_new_flats(self, force: bool) -> List[FlatDetails]:
+        logger.info("Checking for new flats...")
+        now = datetime.now()
+        due = list(self.scrapers) if force else self.due_scrapers(now)
+        for scraper in due:
+            self.next_due[scraper.name] = now + timedelta(
+                seconds=self.scraper_interval(scraper)
             )
+        if len(due) < len(self.scrapers):
+            logger.info(f"Due scrapers: {', '.join(s.name for s in due) or 'none'}")
+        new_flats = await self.fetch_all_flats(due)
+
+        # Find flats that weren't in the previous cache, neither by ID nor by
+        # content (sites may re-publish a listing under a new ID)
+        current_ids = {flat.id for flat in self.current_flats}
+        current_hashes = {flat.content_hash() for flat in self.current_flats}
+        new_entries = []
+        for flat in new_flats:
+            if flat.id in current_ids or flat.content_hash() in current_hashes:
+                continue
+            new_entries.append(flat)
+        # The seen store remembers notified flats that left the listings, until
+        # DEDUP_TTL_DAYS; the database also keeps them across reboots
+        new_entries = [flat for flat in new_entries if not self.store.is_seen(flat.id)]
+        if new_entries:
+            logger.info(f"Found {len(new_entries)} new flats")
+        if new_entries and self.criteria.uses_distance():
+            await self.geocoder.locate_flats(new_entries)
+
+        # Debug output for all new entries
+        if new_entries:
             logger.info(
-                f"Error notification sent to private chat {self.private_chat_id}"
+                f"\n{'='*80}\n🔍 DEBUG: Found {len(new_entries)} new flats, checking filters...\n{'='*80}"
             )
-        except TelegramError as e:
-            logger.error(f"Failed to send error notification: {e}")
+            for flat in new_entries:
+                room_count = flat.room_count()
+                passes_room_filter = room_count is None or (
+                    room_count >= self.criteria.min_rooms
+                    and (self.criteria.max_rooms is None or room_count <= self.criteria.max_rooms)
+                )
+                passes_wbs_filter = not flat.wbs_required or self.criteria.allow_wbs
+                passes_all = flat.meets_criteria(self.criteria)
+
+                status_icon = "✅ PASS" if passes_all else "❌ FILTERED"
+                logger.info(f"\n{status_icon} - {flat.source}")
+                logger.info(f"  Title: {flat.title}")
+                logger.info(f"  Link: {flat.link}")
+                logger.info(
+                    f"  Rooms: {room_count} → {'✓' if passes_room_filter else f'✗ (need {self.criteria.describe_rooms()})'}"
+                )
+                logger.info(
+                    f"  WBS: {'❌ Required' if flat.wbs_required else '✅ Not required'} → {'✓' if passes_wbs_filter else '✗ (filtered)'}"
+                )
+                if flat.details:
+                    logger.info(f"  Details: {flat.details}")
+                logger.info(
+                    f"  → Final: {'WILL NOTIFY USER' if passes_all else 'FILTERED OUT'}"
+                )
+            logger.info(f"\n{'='*80}\n")
+
+        matching_flats = [flat for flat in new_entries if flat.meets_criteria(self.criteria)]
+        # The same flat listed on several websites is only sent once, also when
+        # another website's listing of it was sent in an earlier check
+        sent = self.store.sent_dedup_sources(
+            [flat.dedup_key() for flat in matching_flats if flat.dedup_key() is not None]
+        )
+        matching_flats = drop_cross_source_duplicates(matching_flats, sent)
+        self._record_activity(new_entries, matching_flats)
+        if not matching_flats:
+            logger.info(f"ℹ️  No flats passed filters (all were filtered out)")
+        flats_to_send = self._release_pending(matching_flats, now)
+        if flats_to_send:
+            logger.info(f"✉️  Sending {len(flats_to_send)} flats to user")
+            await self.send_update(flats_to_send)
+            # Mark these flats as seen after successful notification
+            self.store.mark_seen(flats_to_send)
+            self.store.record_notifications(flats_to_send)
+
+        self.store.save_snapshots(new_flats)
+        await self._check_price_changes(new_flats, current_ids)
+
+        # Update the cache, keeping the flats of scrapers that weren't due
+        kept_flats = [
+            flat
+            for flat in self.current_flats
+            if not any(scraper.matches_name(flat.source) for scraper in due)
+        ]
+        self.current_flats = kept_flats + new_flats
+        # Forget rents of flats that are no longer listed
+        listed_ids = {flat.id for flat in self.current_flats}
+        self.known_rents = {
+            flat_id: rent for flat_id, rent in self.known_rents.items() if flat_id in listed_ids
+        }
+        return flats_to_send
+
+    def local_now(self) -> datetime:
+        """The current time in TIMEZONE."""
+        return datetime.now(self.config.timezone)
+
+    def _is_quiet_hours(self, now: Optional[datetime] = None) -> bool:
+        """Whether messages should be sent silently: QUIET_HOURS_START to
+        QUIET_HOURS_END (in TIMEZONE) if configured, otherwise 8 PM to 8 AM."""
+        hour = to_local(now, self.config.timezone).hour if now else self.local_now().hour
+        if self.config.quiet_hours_start is None:
+            return in_hour_window(hour, 20, 8)
+        return in_hour_window(hour, self.config.quiet_hours_start, self.config.quiet_hours_end)
+
+    def _defer_notifications(self, now: datetime) -> bool:
+        """Whether flat notifications are held back, only with configured quiet hours."""
+        return self.config.quiet_hours_start is not None and self._is_quiet_hours(now)
+
+    def _release_pending(
+        self, matching_flats: List[FlatDetails], now: datetime
+    ) -> List[FlatDetails]:
+        """Add matching flats to the pending buffer and return the flats to notify now.
+
+        The buffer is released once it holds MIN_BATCH_SIZE flats or its oldest
+        flat has waited MAX_HOLD_SECONDS. With the default of 1 every match is
+        sent right away. During configured quiet hours everything is held and
+        released by the first check after they end.
+        """
+        pending_ids = {flat.id for flat in self.pending_flats}
+        added = [flat for flat in matching_flats if flat.id not in pending_ids]
+        if added and not self.pending_flats:
+            self.pending_since = now
+        self.pending_flats.extend(added)
+        if not self.pending_flats:
+            return []
+        if self._defer_notifications(now):
+            logger.info(f"Quiet hours, holding {len(self.pending_flats)} flats")
+            return []
+
+        held_for = (now - self.pending_since).total_seconds()
+        if (
+            len(self.pending_flats) < self.config.min_batch_size
+            and held_for < self.config.max_hold_seconds
+        ):
+            logger.info(
+                f"Holding {len(self.pending_flats)} flats until "
+                f"{self.config.min_batch_size} match or {self.config.max_hold_seconds}s pass"
+            )
+            return []
+
+        flats, self.pending_flats, self.pending_since = self.pending_flats, [], None
+        return flats
+
+    def find_price_changes(
+        self, flats: List[FlatDetails], known_ids
+    ) -> List[Tuple[FlatDetails, float, float]]:
+        """Return (flat, old rent, new rent) for known flats whose total rent changed.
+
+        Updates the tracked rents. An unparseable rent keeps the last known value.
+        """
+        changes = []
+        for flat in flats:
+            rent = flat.total_rent()
+            if rent is None:
+                continue
+            old_rent = self.known_rents.get(flat.id)
+            if old_rent is not None and flat.id in known_ids and rent != old_rent:
+                changes.append((flat, old_rent, rent))
+            self.known_rents[flat.id] = rent
+        return changes
+
+    async def _check_price_changes(self, flats: List[FlatDetails], known_ids):
+        for flat, old_rent, new_rent in self.find_price_changes(flats, known_ids):
+            if flat.meets_criteria(self.criteria):
+                await self.send_price_change(flat, old_rent, new_rent)
+
+    async def send_price_change(self, flat: FlatDetails, old_rent: float, new_rent: float):
+        logger.info(f"Price of {flat.id} changed from {old_rent:g} to {new_rent:g}")
+        is_quiet_hours = self._is_quiet_hours()
+
+        await self._broadcast(
+            self.formatter.format_price_change_message(flat, old_rent, new_rent),
+            silent=is_quiet_hours,
+        )
+
+    def _record_activity(self, new_entries: List[FlatDetails], matching: List[FlatDetails]):
+        now = datetime.now()
+        matching_ids = {flat.id for flat in matching}
+        self.recent_activity.extend(
+            (now, flat, flat.id in matching_ids) for flat in new_entries
+        )
+        cutoff = now - timedelta(hours=24)
+        self.recent_activity = [a for a in self.recent_activity if a[0] >= cutoff]
+
+    async def send_daily_summary_if_due(self):
+        """Send the daily summary once per day at DAILY_SUMMARY_HOUR."""
+        hour = self.config.daily_summary_hour
+        now = self.local_now()
+        if hour is None or now.hour != hour:
+            return
+        today = now.date().isoformat()
+        if self.state.get("last_summary_date") == today:
+            return
+        self.state.set("last_summary_date", today)
+
+        # Drop entries older than 24 hours, even if there was no check since
+        self._record_activity([], [])
+        if not self.recent_activity:
+            logger.info("No new flats in the last 24 hours, skipping daily summary")
+            return
+
+        counts: Dict[str, int] = {}
+        for _, flat, _ in self.recent_activity:
+            counts[flat.source] = counts.get(flat.source, 0) + 1
+        top_matches = [flat for _, flat, matched in self.recent_activity if matched][-5:]
+
+        await self._broadcast(self.formatter.format_daily_summary(counts, top_matches))
+        logger.info("Daily summary sent")
+
+    def evict_expired_flats(self, now: Optional[datetime] = None) -> int:
+        """Forget seen flats older than DEDUP_TTL_DAYS, so re-listed ones notify again."""
+        if self.config.dedup_ttl_days is None:
+            return 0
+        evicted = self.store.evict_seen(timedelta(days=self.config.dedup_ttl_days), now)
+        if evicted:
+            logger.info(f"Forgot {evicted} flats seen more than {self.config.dedup_ttl_days:g} days ago")
+        return evicted
 
     async def monitor(self):
         logger.info("Starting monitoring loop...")
 
-        # Load seen flats cache to prevent duplicates across restarts
-        load_seen_flats()
-
-        await self.send_welcome()
+        try:
+            await self.send_welcome()
+        except Exception as e:
+            # A failed welcome must not keep the bot from monitoring
+            logger.error(f"Failed to send welcome message: {e}")
 
-        # Don't do initial fetch - let the first loop iteration handle it
-        # This ensures we check for new flats on startup too
-        self.current_flats = []
+        # Don't do initial fetch - let the first loop iteration handle it.
+        # Flats restored from STATE_FILE serve as the baseline, without one
+        # every listed flat is reported on startup.
 
         while True:
             try:
-                logger.info("Checking for new flats...")
-                new_flats = await self.fetch_all_flats()
-
-                # Find flats that weren't in the previous cache
-                current_ids = {flat.id for flat in self.current_flats}
-                new_entries = [flat for flat in new_flats if flat.id not in current_ids]
-                if new_entries:
-                    logger.info(f"Found {len(new_entries)} new flats")
-
-                # Filter for flats with 2 or more rooms and not requiring WBS
-                def get_room_count(flat):
-                    # Use standardized field name
-                    if StandardFields.ROOMS in flat.details:
-                        try:
-                            # Handle different formats: "2", "2 Zimmer", "2.0", etc.
-                            room_str = flat.details[StandardFields.ROOMS].lower()
-                            # Extract first number from string
-                            import re
-
-                            match = re.search(r"\d+(?:[.,]\d+)?", room_str)
-                            if match:
-                                return float(match.group().replace(",", "."))
-                        except (ValueError, AttributeError):
-                            pass
-                    return 0  # Return 0 if no valid room count found
-
-                # Debug output for all new entries
-                if new_entries:
-                    logger.info(
-                        f"\n{'='*80}\n🔍 DEBUG: Found {len(new_entries)} new flats, checking filters...\n{'='*80}"
-                    )
-                    for flat in new_entries:
-                        room_count = get_room_count(flat)
-                        passes_room_filter = room_count == 0 or room_count >= 2
-                        passes_wbs_filter = not flat.wbs_required
-                        passes_all = passes_room_filter and passes_wbs_filter
-
-                        status_icon = "✅ PASS" if passes_all else "❌ FILTERED"
-                        logger.info(f"\n{status_icon} - {flat.source}")
-                        logger.info(f"  Title: {flat.title}")
-                        logger.info(f"  Link: {flat.link}")
-                        logger.info(
-                            f"  Rooms: {room_count} → {'✓' if passes_room_filter else '✗ (need 2+)'}"
-                        )
-                        logger.info(
-                            f"  WBS: {'❌ Required' if flat.wbs_required else '✅ Not required'} → {'✓' if passes_wbs_filter else '✗ (filtered)'}"
-                        )
-                        if flat.details:
-                            logger.info(f"  Details: {flat.details}")
-                        logger.info(
-                            f"  → Final: {'WILL NOTIFY USER' if passes_all else 'FILTERED OUT'}"
-                        )
-                    logger.info(f"\n{'='*80}\n")
-
-                two_or_more_rooms = [
-                    flat
-                    for flat in new_entries
-                    if (get_room_count(flat) == 0 or get_room_count(flat) >= 2)
-                    and not flat.wbs_required
-                ]
-                if two_or_more_rooms:
-                    logger.info(f"✉️  Sending {len(two_or_more_rooms)} flats to user")
-                    await self.send_update(two_or_more_rooms)
-                    # Mark these flats as seen in the global cache after successful notification
-                    mark_flats_as_seen(two_or_more_rooms)
-                    # Cache will auto-save when threshold is reached (see scrapers.py)
-                    # Only force-save on shutdown to minimize RAM disk writes
-                    save_seen_flats()  # This will only write if threshold reached
-                else:
-                    logger.info(f"ℹ️  No flats passed filters (all were filtered out)")
-
-                # Update the cache
-                self.current_flats = new_flats
-
+                await self.check_for_new_flats()
+                # Only counts as healthy if at least one website could be scraped
+                if "Available" in self.website_statuses.values():
+                    self.last_successful_cycle = datetime.now()
             except Exception as e:
                 error_msg = f"Error during monitoring: {str(e)}"
                 logger.error(error_msg)
-                await self.send_error_notification(error_msg)
+                await self._notify_error("Monitoring", error_msg)
+            else:
+                await self._notify_recovered("Monitoring")
+                if not self.self_test_done:
+                    self.self_test_done = True
+                    await self._report_empty_scrapers()
 
-            logger.info(
-                f"Waiting {self.config.monitor_interval} seconds before next check..."
-            )
-            await asyncio.sleep(self.config.monitor_interval)
+            try:
+                await self.send_daily_summary_if_due()
+            except Exception as e:
+                logger.error(f"Failed to send daily summary: {e}")
+
+            try:
+                self.evict_expired_flats()
+            except Exception as e:
+                logger.error(f"Failed to evict expired flats: {e}")
+
+            wait_seconds = self._seconds_until_next_due()
+            logger.info(f"Waiting {wait_seconds:.0f} seconds before next check...")
+            await asyncio.sleep(wait_seconds)
+
+    def empty_scrapers(self) -> List:
+        """Scrapers that fetched successfully but found no flats, often a sign of changed markup."""
+        return [
+            scraper
+            for scraper in self.scrapers
+            if self.website_statuses.get(scraper.name) == "Available"
+            and scraper.last_item_count == 0
+        ]
+
+    async def _report_empty_scrapers(self):
+        """Startup self-test: tell the private chat once which scrapers returned nothing.
+
+        Scrapers that failed are left out, their errors were reported already.
+        """
+        empty = self.empty_scrapers()
+        if not empty:
+            return
+        lines = []
+        for scraper in empty:
+            status = f"HTTP {scraper.last_status}" if scraper.last_status else "no HTTP status"
+            logger.warning(f"Self-test: {scraper.name} returned no flats ({status})")
+            lines.append(f"• {html.escape(scraper.name)} ({status})")
+        message = (
+            "⚠️ <b>Startup self-test</b>\n\n"
+            "These websites were reachable but returned no flats. "
+            "If they usually list some, their page layout may have changed:\n"
+            + "\n".join(lines)
+        )
+        await self._send_admin(message)
+
+    def is_healthy(self) -> bool:
+        """True if a monitoring cycle scraped successfully within the last two intervals.
+
+        The longest scraper interval counts: the loop sleeps until the next
+        scraper is due, which with SCRAPER_INTERVALS can be longer than
+        MONITOR_INTERVAL.
+        """
+        if self.last_successful_cycle is None:
+            return False
+        longest = max(
+            (self.scraper_interval(scraper) for scraper in self.scrapers),
+            default=self.config.monitor_interval,
+        )
+        max_age = timedelta(seconds=2 * longest)
+        return datetime.now() - self.last_successful_cycle <= max_age
 
     async def test_command(self, update: Update, context: ContextTypes.DEFAULT_TYPE):
-        """Handles the /test command to return the first result of each scraper."""
-        if str(update.effective_chat.id) != self.chat_id:
+        """Handles the /test [scraper] command to return the first result of each scraper."""
+        if not await self._is_authorized(update):
             return
 
-        # Check if current time is within allowed hours (8 AM - 8 PM)
-        current_hour = datetime.now().hour
-        is_quiet_hours = not (8 <= current_hour < 20)
+        is_quiet_hours = self._is_quiet_hours()
 
-        reset_seen_flats()
-        message = "🏠 <b>Test Results</b>\n\n"
+        scrapers = self.scrapers
+        if context.args:
+            name = " ".join(context.args).strip()
+            scrapers = [s for s in self.scrapers if s.matches_name(name)]
+            if not scrapers:
+                valid_names = ", ".join(scraper.name for scraper in self.scrapers)
+                try:
+                    await update.message.reply_text(
+                        f"Unknown scraper: {name}\nValid scrapers: {valid_names}",
+                        disable_notification=is_quiet_hours,
+                    )
+                except TelegramError as e:
+                    logger.error(f"Failed to send test usage: {e}")
+                return
 
-        for scraper in self.scrapers:
+        # Only reads: the seen flats stay untouched, so the next check doesn't
+        # report everything again. fetch_all drops duplicates within its own batch.
+        sections = []
+        for scraper in scrapers:
             try:
-                flats = await scraper.fetch_flats()
+                flats = await scraper.fetch_all()
                 if flats:
-                    flat = flats[0]
-                    message += f"<b>{scraper.__class__.__name__}</b>\n"
-                    message += self.formatter.format_flat_message(flat)
-                    message += "\n"
+                    sections.append(
+                        f"<b>{scraper.name}</b>\n{self.formatter.format_flat_message(flats[0])}"
+                    )
                 else:
-                    message += f"<b>{scraper.__class__.__name__}</b>\n_No flats found_\n\n"
+                    sections.append(f"<b>{scraper.name}</b>\n<i>No flats found</i>\n")
             except Exception as e:
-                message += f"<b>{scraper.__class__.__name__}</b>\n_Error: {str(e)}_\n\n"
-                logger.error(f"Test failed for {scraper.__class__.__name__}: {e}")
+                sections.append(f"<b>{scraper.name}</b>\n<i>Error: {html.escape(str(e))}</i>\n")
+                logger.error(f"Test failed for {scraper.name}: {e}")
 
-        try:
-            await update.message.reply_text(
-                text=message,
-                parse_mode="HTML",
-                disable_web_page_preview=True,
-                disable_notification=is_quiet_hours,
+        if not sections:
+            return
+        sections[0] = "🏠 <b>Test Results</b>\n\n" + sections[0]
+        # Split into several messages if needed; one that fails doesn't stop the others
+        messages = self.formatter.batch_messages(sections, len(sections))
+        sent = await self._reply_each(
+            update,
+            messages,
+            "test results",
+            parse_mode="HTML",
+            disable_web_page_preview=True,
+            disable_notification=is_quiet_hours,
+        )
+        if sent < len(messages):
+            await self.send_error_notification(
+                f"Failed to send {len(messages) - sent} of {len(messages)} test result messages"
             )
-        except TelegramError as e:
-            logger.error(f"Failed to send test results: {e}")
-            await self.send_error_notification(f"Failed to send test results: {e}")
 
     async def handle_clear_command(
         self, update: Update, context: ContextTypes.DEFAULT_TYPE
     ):
-        if str(update.effective_chat.id) != self.chat_id:
+        if not await self._is_authorized(update):
             return
 
-        # Check if current time is within allowed hours (8 AM - 8 PM)
-        current_hour = datetime.now().hour
-        is_quiet_hours = not (8 <= current_hour < 20)
+        is_quiet_hours = self._is_quiet_hours()
 
         try:
             self.current_flats = []
-            reset_seen_flats()
+            self.store.reset_seen()
             await update.message.reply_text(
                 "✅ All caches cleared successfully!",
                 disable_notification=is_quiet_hours,
@@ -692,43 +2378,446 @@ class FlatMonitor:
                 f"Failed to send clear confirmation: {e}"
             )
 
+    async def handle_filter_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /filter command to show or change the filters at runtime."""
+        if not await self._is_authorized(update):
+            return
+
+        usage = (
+            "Usage: /filter [name value]\n"
+            "Filters: min_rooms, max_rooms, min_area, max_rent, min_floor, max_floor, "
+            "max_floor_without_elevator, available_before, "
+            "max_distance_km, center_lat, center_lon, allow_wbs, require_balcony, require_elevator, "
+            "districts, include_keywords, exclude_keywords\n"
+            "Examples: /filter min_rooms 3, /filter districts Wedding,Mitte, "
+            "/filter available_before 01.03.2025, /filter max_rent none"
+        )
+
+        try:
+            if context.args:
+                if len(context.args) < 2:
+                    await update.message.reply_text(usage)
+                    return
+
+                name = context.args[0].lower()
+                value = " ".join(context.args[1:])
+                try:
+                    self.criteria.set_value(name, value)
+                except ValueError as e:
+                    await update.message.reply_text(f"❌ {e}\n\n{usage}")
+                    return
+
+                self.state.set("filters", self.criteria.to_dict())
+                logger.info(f"Filter {name} changed to {value}")
+
+            await update.message.reply_text(
+                self.formatter.format_filter_message(self.criteria),
+                parse_mode="HTML",
+            )
+        except TelegramError as e:
+            logger.error(f"Failed to send filter message: {e}")
+
+    async def handle_recent_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /recent [n] command to show the last notified flats."""
+        if not await self._is_authorized(update):
+            return
+
+        try:
+            count = 10
+            if context.args:
+                try:
+                    count = max(1, int(context.args[0]))
+                except ValueError:
+                    await update.message.reply_text(
+                        f"Usage: /recent [n] (up to {RECENT_FLATS_LIMIT})"
+                    )
+                    return
+
+            if not self.recent_flats:
+                await update.message.reply_text("No flats have been notified yet.")
+                return
+
+            recent = list(reversed(self.recent_flats))[:count]
+            await update.message.reply_text(
+                text=self.formatter.format_recent_message(recent, self.config.timezone),
+                parse_mode="HTML",
+                disable_web_page_preview=True,
+            )
+        except TelegramError as e:
+            logger.error(f"Failed to send recent flats: {e}")
+
+    async def handle_sources_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /sources command to list the active scrapers and their URLs."""
+        if not await self._is_authorized(update):
+            return
+
+        try:
+            await update.message.reply_text(
+                text=self.formatter.format_sources_message(self.scrapers),
+                parse_mode="HTML",
+                disable_web_page_preview=True,
+            )
+        except TelegramError as e:
+            logger.error(f"Failed to send sources: {e}")
+
+    async def handle_details_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /details command to show everything known about one cached flat."""
+        if not await self._is_authorized(update):
+            return
+
+        try:
+            if not context.args:
+                await update.message.reply_text(
+                    "Usage: /details <id>\nThe ID is shown below each flat in /list."
+                )
+                return
+
+            flat_id = " ".join(context.args).strip()
+            flat = next((f for f in self.current_flats if f.id == flat_id), None)
+            if flat is None:
+                await update.message.reply_text(f"Flat {flat_id} not found in the cache.")
+                return
+
+            await update.message.reply_text(
+                text=self.formatter.format_flat_details(flat),
+                parse_mode="HTML",
+                disable_web_page_preview=True,
+            )
+        except TelegramError as e:
+            logger.error(f"Failed to send flat details: {e}")
+
+    async def handle_save_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /save command to bookmark a cached flat in this chat's favorites."""
+        if not await self._is_authorized(update):
+            return
+
+        chat_id = str(update.effective_chat.id)
+        try:
+            if not context.args:
+                await update.message.reply_text(
+                    "Usage: /save <id>\nThe ID is shown below each flat in /list."
+                )
+                return
+
+            flat_id = " ".join(context.args).strip()
+            favorites = self.favorites.setdefault(chat_id, [])
+            if any(f.id == flat_id for f in favorites):
+                await update.message.reply_text(f"ℹ️ Flat {flat_id} is already saved.")
+                return
+            flat = next((f for f in self.current_flats if f.id == flat_id), None)
+            if flat is None:
+                await update.message.reply_text(f"Flat {flat_id} not found in the cache.")
+                return
+
+            favorites.append(flat)
+            self.state.set(
+                "favorites",
+                {
+                    chat: [f.to_dict() for f in flats]
+                    for chat, flats in self.favorites.items()
+                    if flats
+                },
+            )
+            logger.info(f"Chat {chat_id} saved flat {flat_id}")
+            await update.message.reply_text(
+                f"⭐ Saved {flat.title}. Show your favorites with /favorites."
+            )
+        except TelegramError as e:
+            logger.error(f"Failed to send save confirmation: {e}")
+
+    async def handle_favorites_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /favorites command to list this chat's bookmarked flats."""
+        if not await self._is_authorized(update):
+            return
+
+        favorites = self.favorites.get(str(update.effective_chat.id), [])
+        try:
+            if not favorites:
+                await update.message.reply_text(
+                    "No favorites yet. Bookmark a flat with /save <id>."
+                )
+                return
+
+            await update.message.reply_text(f"⭐ {len(favorites)} saved flats")
+            for flat in favorites:
+                await update.message.reply_text(
+                    text=self.formatter.format_flat_message(flat),
+                    parse_mode="HTML",
+                    disable_web_page_preview=True,
+                )
+        except TelegramError as e:
+            logger.error(f"Failed to send favorites: {e}")
+
+    async def handle_export_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /export command to send the current flats as a JSON file."""
+        if not await self._is_authorized(update):
+            return
+
+        try:
+            if not self.current_flats:
+                await update.message.reply_text(
+                    "📭 No flats cached yet, try again after the next check."
+                )
+                return
+
+            data = json.dumps(
+                [flat.to_dict() for flat in self.current_flats],
+                indent=2,
+                ensure_ascii=False,
+            )
+            timestamp = datetime.now(timezone.utc).strftime("%Y%m%dT%H%M%SZ")
+            await update.message.reply_document(
+                document=io.BytesIO(data.encode("utf-8")),
+                filename=f"flats-{timestamp}.json",
+                caption=f"📦 {len(self.current_flats)} flats",
+            )
+            logger.info(f"Exported {len(self.current_flats)} flats")
+        except TelegramError as e:
+            logger.error(f"Failed to send export: {e}")
+
+    async def handle_subscribe_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /subscribe command to receive flat notifications in this chat."""
+        chat_id = str(update.effective_chat.id)
+
+        try:
+            if is_chat_in(update.effective_chat, self.chat_ids):
+                await update.message.reply_text(
+                    "ℹ️ This chat is configured to receive notifications already."
+                )
+                return
+            if not self.config.allow_subscriptions:
+                await update.message.reply_text("❌ Subscriptions are disabled for this bot.")
+                return
+            if chat_id in self.subscribers:
+                await update.message.reply_text("ℹ️ This chat is already subscribed.")
+                return
+
+            self.subscribers.append(chat_id)
+            self.state.set("subscribers", self.subscribers)
+            logger.info(f"Chat {chat_id} subscribed")
+            await update.message.reply_text(
+                "✅ Subscribed! New flats will be sent to this chat.\n"
+                "Use /unsubscribe to stop."
+            )
+        except TelegramError as e:
+            logger.error(f"Failed to send subscribe confirmation: {e}")
+
+    async def handle_unsubscribe_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /unsubscribe command to stop notifications in this chat."""
+        chat_id = str(update.effective_chat.id)
+
+        try:
+            if chat_id not in self.subscribers:
+                await update.message.reply_text("ℹ️ This chat is not subscribed.")
+                return
+
+            self.subscribers.remove(chat_id)
+            self.state.set("subscribers", self.subscribers)
+            logger.info(f"Chat {chat_id} unsubscribed")
+            await update.message.reply_text("✅ Unsubscribed, no more flats will be sent here.")
+        except TelegramError as e:
+            logger.error(f"Failed to send unsubscribe confirmation: {e}")
+
+    async def handle_whoami_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the /whoami command; answers in any chat, to help find CHAT_ID."""
+        text = self.formatter.format_whoami_message(update.effective_chat, update.effective_user)
+        try:
+            # Plain text, usernames may contain characters HTML would need escaped
+            await update.message.reply_text(text)
+        except TelegramError as e:
+            logger.error(f"Failed to send whoami reply: {e}")
+
+    async def handle_retry_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the admin-only /retry command to clear a scraper's backoff."""
+        if not await self._is_authorized(update, [self.private_chat_id]):
+            return
+
+        valid_names = ", ".join(scraper.name for scraper in self.scrapers)
+        try:
+            if not context.args:
+                await update.message.reply_text(
+                    f"Usage: /retry <scraper>\nValid scrapers: {valid_names}"
+                )
+                return
+
+            name = " ".join(context.args).strip()
+            scraper = next((s for s in self.scrapers if s.matches_name(name)), None)
+            if scraper is None:
+                await update.message.reply_text(
+                    f"Unknown scraper: {name}\nValid scrapers: {valid_names}"
+                )
+                return
+
+            scraper.clear_backoff()
+            logger.info(f"Backoff cleared for {scraper.name}")
+            await update.message.reply_text(
+                f"✅ Backoff cleared for {scraper.name}, it will be retried on the next check."
+            )
+        except TelegramError as e:
+            logger.error(f"Failed to send retry confirmation: {e}")
+
+    async def handle_reload_command(
+        self, update: Update, context: ContextTypes.DEFAULT_TYPE
+    ):
+        """Handles the admin-only /reload command to re-read config.json."""
+        if not await self._is_authorized(update, [self.private_chat_id]):
+            return
+
+        try:
+            try:
+                applied, needs_restart = self.reload_config()
+            except RuntimeError as e:
+                logger.error(f"Reload failed: {e}")
+                await update.message.reply_text(
+                    f"❌ {e}\nThe current configuration is still in use."
+                )
+                return
+
+            if not applied and not needs_restart:
+                await update.message.reply_text("No configuration changes found.")
+                return
+
+            lines = ["🔄 Configuration reloaded"]
+            if applied:
+                lines.append(f"Applied: {', '.join(applied)}")
+                if "criteria" in applied and self.state.get("filters"):
+                    lines.append("Filters set via /filter still take precedence.")
+            if needs_restart:
+                lines.append(f"Restart required for: {', '.join(needs_restart)}")
+            await update.message.reply_text("\n".join(lines))
+        except TelegramError as e:
+            logger.error(f"Failed to send reload confirmation: {e}")
+
 
 async def main():
+    monitor = None
     try:
         config = Config()
         monitor = FlatMonitor(config)
 
-        application = (
-            Application.builder()
-            .token(config.bot_token)
-            .concurrent_updates(True)
-            .build()
-        )
+        builder = Application.builder().token(config.bot_token).concurrent_updates(True)
+        if config.telegram_proxy_url:
+            builder = builder.proxy(config.telegram_proxy_url).get_updates_proxy(
+                config.telegram_proxy_url
+            )
+        application = builder.build()
         monitor.application = application
 
         application.add_handler(CommandHandler("list", monitor.handle_list_command))
         application.add_handler(CommandHandler("help", monitor.handle_help_command))
         application.add_handler(CommandHandler("status", monitor.handle_status_command))
+        application.add_handler(CommandHandler("nextcheck", monitor.handle_nextcheck_command))
+        application.add_handler(CommandHandler("scan", monitor.handle_scan_command))
         application.add_handler(CommandHandler("test", monitor.test_command))
+        application.add_handler(CommandHandler("sources", monitor.handle_sources_command))
         application.add_handler(CommandHandler("clear", monitor.handle_clear_command))
+        application.add_handler(CommandHandler("filter", monitor.handle_filter_command))
+        application.add_handler(CommandHandler("retry", monitor.handle_retry_command))
+        application.add_handler(CommandHandler("reload", monitor.handle_reload_command))
+        application.add_handler(CommandHandler("export", monitor.handle_export_command))
+        application.add_handler(CommandHandler("details", monitor.handle_details_command))
+        application.add_handler(CommandHandler("recent", monitor.handle_recent_command))
+        application.add_handler(CommandHandler("save", monitor.handle_save_command))
+        application.add_handler(
+            CommandHandler("favorites", monitor.handle_favorites_command)
+        )
+        application.add_handler(
+            CommandHandler("subscribe", monitor.handle_subscribe_command)
+        )
+        application.add_handler(
+            CommandHandler("unsubscribe", monitor.handle_unsubscribe_command)
+        )
+        application.add_handler(CommandHandler("whoami", monitor.handle_whoami_command))
+
+        # SIGINT (Ctrl+C) and SIGTERM (systemd stop) trigger a graceful shutdown
+        stop_event = asyncio.Event()
+        loop = asyncio.get_running_loop()
+        for sig in (signal.SIGINT, signal.SIGTERM):
+            try:
+                loop.add_signal_handler(sig, stop_event.set)
+            except NotImplementedError:
+                # Not supported on Windows, KeyboardInterrupt still works there
+                pass
 
         monitoring_task = asyncio.create_task(monitor.monitor())
+        http_runners = []
+        if config.metrics_port and config.metrics_port == config.health_port:
+            http_runners.append(
+                await start_http_server(
+                    config.metrics_port, monitor.metrics, monitor.is_healthy
+                )
+            )
+        else:
+            if config.metrics_port:
+                http_runners.append(
+                    await start_http_server(config.metrics_port, metrics=monitor.metrics)
+                )
+            if config.health_port:
+                http_runners.append(
+                    await start_http_server(
+                        config.health_port, health_check=monitor.is_healthy
+                    )
+                )
 
         await application.initialize()
         await application.start()
 
         try:
-            logger.info("Starting polling...")
-            await application.updater.start_polling(
-                allowed_updates=Update.ALL_TYPES, drop_pending_updates=True
-            )
-            logger.info("Polling started successfully")
+            # Both modes feed the same command handlers registered above
+            if config.telegram_mode == "webhook":
+                logger.info(f"Starting webhook on port {config.webhook_port}...")
+                await application.updater.start_webhook(
+                    listen=config.webhook_listen,
+                    port=config.webhook_port,
+                    url_path=urlparse(config.webhook_url).path.lstrip("/"),
+                    webhook_url=config.webhook_url,
+                    secret_token=config.webhook_secret,
+                    allowed_updates=Update.ALL_TYPES,
+                    drop_pending_updates=True,
+                )
+                logger.info("Webhook started successfully")
+            else:
+                logger.info("Starting polling...")
+                # The long-poll timeout; the read timeout of getUpdates is extended by it
+                await application.updater.start_polling(
+                    timeout=config.telegram_timeout,
+                    allowed_updates=Update.ALL_TYPES,
+                    drop_pending_updates=True,
+                )
+                logger.info("Polling started successfully")
 
-            while True:
-                await asyncio.sleep(1)
+            await stop_event.wait()
+            logger.info("Shutdown requested")
         except Exception as e:
             logger.error(f"Error during polling: {e}")
         finally:
+            # Stop fetching updates first, then let in-flight commands finish
+            # before the HTTP session they may use is closed
+            if application.updater.running:
+                await application.updater.stop()
+            await application.stop()
             monitoring_task.cancel()
             try:
                 await monitoring_task
@@ -736,15 +2825,21 @@ async def main():
                 pass
             # Save cache on shutdown
             logger.info("Shutting down, saving cache...")
-            save_seen_flats(force=True)
+            monitor.store.close()
+            await monitor.send_queue.close()
+            monitor.save_current_flats()
+            for runner in http_runners:
+                await runner.cleanup()
             # Close aiohttp session
             await close_session()
-            await application.stop()
+            await application.shutdown()
+            logger.info("Shutdown complete")
 
     except Exception as e:
         logger.error(f"Bot stopped due to error: {e}")
-        # Save cache even on error
-        save_seen_flats(force=True)
+        # Save seen flats even on error; closing twice is harmless
+        if monitor is not None:
+            monitor.store.close()
         # Close aiohttp session
         await close_session()
 
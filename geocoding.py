@@ -0,0 +1,87 @@
+"""Address geocoding for the distance filter (MAX_DISTANCE_KM).
+
+Addresses are resolved with a Nominatim search endpoint (GEOCODING_URL).
+Results, including addresses that could not be found, are cached in the
+state file, so each address is only looked up once. Requests are spaced one
+second apart, as required by the public Nominatim usage policy.
+"""
+
+import asyncio
+import logging
+from typing import Dict, List, Optional, Tuple
+
+import aiohttp
+
+from scrapers import FlatDetails, StandardFields
+from scrapers.session import RateLimiter, get_session
+from state import StateStore
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_GEOCODING_URL = "https://nominatim.openstreetmap.org/search"
+# Nominatim asks clients to identify themselves
+USER_AGENT = "BerlinFlatBot/1.0"
+
+
+class Geocoder:
+    def __init__(self, url: str, state: StateStore):
+        self.url = url
+        self.state = state
+        # Address -> [lat, lon], or None if it couldn't be resolved
+        self.cache: Dict[str, Optional[List[float]]] = dict(state.get("geocodes", {}))
+        self._rate_limiter = RateLimiter(60)
+
+    @staticmethod
+    def query_for(flat: FlatDetails) -> Optional[str]:
+        """Search query for a flat's address, None if it has none."""
+        address = (flat.details.get(StandardFields.ADDRESS) or "").strip()
+        if not address:
+            return None
+        if "berlin" not in address.lower():
+            address += ", Berlin"
+        return address
+
+    async def locate(self, query: str) -> Optional[Tuple[float, float]]:
+        """Coordinates of an address, from the cache or the geocoding service.
+
+        Failed requests are not cached, so the address is tried again later.
+        """
+        if query in self.cache:
+            cached = self.cache[query]
+            return tuple(cached) if cached else None
+
+        await self._rate_limiter.acquire()
+        session = await get_session()
+        try:
+            async with session.get(
+                self.url,
+                params={"q": query, "format": "json", "limit": "1"},
+                headers={"User-Agent": USER_AGENT},
+            ) as response:
+                if response.status != 200:
+                    logger.warning(f"Geocoding '{query}' failed with status {response.status}")
+                    return None
+                results = await response.json(content_type=None)
+        except (aiohttp.ClientError, asyncio.TimeoutError, ValueError) as e:
+            logger.warning(f"Geocoding '{query}' failed: {e}")
+            return None
+
+        location = None
+        if results:
+            location = (float(results[0]["lat"]), float(results[0]["lon"]))
+        else:
+            logger.info(f"Address not found by geocoder: {query}")
+        self.cache[query] = list(location) if location else None
+        return location
+
+    async def locate_flats(self, flats: List[FlatDetails]):
+        """Set the location of flats that don't have one yet and persist new results."""
+        cached_before = len(self.cache)
+        for flat in flats:
+            if flat.location is not None:
+                continue
+            query = self.query_for(flat)
+            if query:
+                flat.location = await self.locate(query)
+        if len(self.cache) != cached_before:
+            self.state.set("geocodes", self.cache)
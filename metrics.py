@@ -0,0 +1,100 @@
+"""Prometheus metrics (METRICS_PORT) and health check (HEALTH_PORT) HTTP endpoints."""
+
+import logging
+from collections import defaultdict
+from typing import Callable, Dict, Iterable, Optional
+
+from aiohttp import web
+
+logger = logging.getLogger(__name__)
+
+
+class Metrics:
+    """In-process counters and gauges, rendered in the Prometheus text format."""
+
+    def __init__(self):
+        self.flats_scraped: Dict[str, int] = defaultdict(int)
+        self.scraper_errors: Dict[str, int] = defaultdict(int)
+        self.scraper_backoff: Dict[str, int] = {}
+        self.notifications_sent = 0
+
+    def record_fetch(self, source: str, flat_count: int):
+        self.flats_scraped[source] += flat_count
+
+    def record_error(self, source: str):
+        self.scraper_errors[source] += 1
+
+    def record_notifications(self, count: int = 1):
+        self.notifications_sent += count
+
+    def update_backoff(self, scrapers: Iterable):
+        for scraper in scrapers:
+            self.scraper_backoff[scraper.name] = int(scraper.in_backoff)
+
+    def render(self) -> str:
+        lines = []
+
+        def add(name: str, kind: str, help_text: str, values: Dict[str, int]):
+            lines.append(f"# HELP {name} {help_text}")
+            lines.append(f"# TYPE {name} {kind}")
+            for source, value in sorted(values.items()):
+                lines.append(f'{name}{{source="{source}"}} {value}')
+
+        add(
+            "flatbot_flats_scraped_total",
+            "counter",
+            "Flats returned by each source.",
+            self.flats_scraped,
+        )
+        add(
+            "flatbot_scraper_errors_total",
+            "counter",
+            "Failed fetches per source.",
+            self.scraper_errors,
+        )
+        add(
+            "flatbot_scraper_backoff",
+            "gauge",
+            "1 while a source is in its backoff period.",
+            self.scraper_backoff,
+        )
+        lines.append("# HELP flatbot_notifications_sent_total Flat notifications sent.")
+        lines.append("# TYPE flatbot_notifications_sent_total counter")
+        lines.append(f"flatbot_notifications_sent_total {self.notifications_sent}")
+        return "\n".join(lines) + "\n"
+
+
+async def start_http_server(
+    port: int,
+    metrics: Optional[Metrics] = None,
+    health_check: Optional[Callable[[], bool]] = None,
+) -> web.AppRunner:
+    """Serve /metrics and/or /healthz on the given port.
+
+    /healthz answers 200 while health_check() is true and 503 otherwise.
+    Returns the runner to clean up on shutdown.
+    """
+
+    async def handle_metrics(request: web.Request) -> web.Response:
+        return web.Response(
+            text=metrics.render(), content_type="text/plain", charset="utf-8"
+        )
+
+    async def handle_health(request: web.Request) -> web.Response:
+        if health_check():
+            return web.Response(text="ok\n")
+        return web.Response(status=503, text="monitoring stalled\n")
+
+    app = web.Application()
+    paths = []
+    if metrics is not None:
+        app.router.add_get("/metrics", handle_metrics)
+        paths.append("/metrics")
+    if health_check is not None:
+        app.router.add_get("/healthz", handle_health)
+        paths.append("/healthz")
+    runner = web.AppRunner(app)
+    await runner.setup()
+    await web.TCPSite(runner, port=port).start()
+    logger.info(f"HTTP server listening on port {port}: {', '.join(paths)}")
+    return runner
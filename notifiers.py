@@ -0,0 +1,130 @@
+"""Notification backends (NOTIFIERS).
+
+Messages are formatted once, as Telegram HTML, by MessageFormatter. Each
+backend sends them to its users: TelegramNotifier (in bot.py) to the
+configured chats and subscribers, DiscordNotifier to a Discord webhook.
+Commands are only available on Telegram.
+"""
+
+import asyncio
+import html
+import logging
+import re
+from typing import List, Optional
+
+import aiohttp
+
+from scrapers.session import get_session
+
+logger = logging.getLogger(__name__)
+
+# Discord rejects webhook messages longer than this many characters
+DISCORD_MAX_LENGTH = 2000
+# Message flag that delivers a message without a push notification
+DISCORD_SUPPRESS_NOTIFICATIONS = 1 << 12
+
+
+class Notifier:
+    """A channel users are notified through."""
+
+    name = "notifier"
+
+    async def broadcast(self, text: str, silent: bool = False) -> List[Exception]:
+        """Send an HTML message to all users and return the errors of failed sends."""
+        raise NotImplementedError
+
+    async def broadcast_wbs(self, text: str, silent: bool = False) -> List[Exception]:
+        """Send a flat that requires a WBS under WBS_POLICY separate_chat.
+
+        Backends without a separate WBS target send it like any other message.
+        """
+        return await self.broadcast(text, silent=silent)
+
+    async def send_admin(self, text: str, silent: bool = False):
+        """Send an HTML message about errors or diagnostics to the operator.
+
+        Raises on failure.
+        """
+        raise NotImplementedError
+
+
+def html_to_markdown(text: str) -> str:
+    """Convert the Telegram HTML subset used by MessageFormatter to Discord markdown."""
+    text = re.sub(
+        r"""<a\s[^>]*?href=["']([^"']*)["'][^>]*>(.*?)</a>""",
+        lambda m: f"[{m.group(2)}]({m.group(1)})",
+        text,
+        flags=re.DOTALL,
+    )
+    for tag, mark in (("b", "**"), ("strong", "**"), ("i", "*"), ("em", "*"), ("code", "`")):
+        text = re.sub(rf"</?{tag}>", mark, text)
+    return html.unescape(re.sub(r"<[^>]+>", "", text))
+
+
+def split_text(text: str, max_length: int) -> List[str]:
+    """Split text at line breaks into parts of at most max_length characters."""
+    parts, current = [], ""
+    for line in text.split("\n"):
+        while len(line) > max_length:
+            if current:
+                parts.append(current)
+                current = ""
+            parts.append(line[:max_length])
+            line = line[max_length:]
+        candidate = f"{current}\n{line}" if current else line
+        if len(candidate) > max_length:
+            parts.append(current)
+            current = line
+        else:
+            current = candidate
+    if current:
+        parts.append(current)
+    return parts
+
+
+class DiscordNotifier(Notifier):
+    """Posts to a Discord channel webhook (DISCORD_WEBHOOK_URL).
+
+    Operator messages go to DISCORD_ADMIN_WEBHOOK_URL, or to the same webhook
+    if that isn't set.
+    """
+
+    name = "discord"
+
+    def __init__(self, webhook_url: str, admin_webhook_url: Optional[str] = None, dry_run: bool = False):
+        self.webhook_url = webhook_url
+        self.admin_webhook_url = admin_webhook_url or webhook_url
+        self.dry_run = dry_run
+
+    async def broadcast(self, text: str, silent: bool = False) -> List[Exception]:
+        try:
+            await self._post(self.webhook_url, text, silent)
+        except (aiohttp.ClientError, asyncio.TimeoutError) as e:
+            logger.error(f"Failed to send Discord message: {e}")
+            return [e]
+        return []
+
+    async def send_admin(self, text: str, silent: bool = False):
+        await self._post(self.admin_webhook_url, text, silent)
+
+    async def _post(self, url: str, text: str, silent: bool):
+        content = html_to_markdown(text)
+        if self.dry_run:
+            logger.info(f"[dry run] Discord message:\n{content}")
+            return
+        session = await get_session()
+        for part in split_text(content, DISCORD_MAX_LENGTH):
+            payload = {"content": part, "allowed_mentions": {"parse": []}}
+            if silent:
+                payload["flags"] = DISCORD_SUPPRESS_NOTIFICATIONS
+            for attempt in range(2):
+                async with session.post(url, json=payload) as response:
+                    if response.status == 429 and attempt == 0:
+                        # Rate limited, Discord says how long to wait
+                        data = await response.json(content_type=None)
+                        delay = float(data.get("retry_after", 1))
+                        logger.warning(f"Rate limited by Discord, retrying in {delay:g}s")
+                        await asyncio.sleep(delay)
+                        continue
+                    response.raise_for_status()
+                    break
@@ -5,17 +5,34 @@ This package contains web scrapers for various Berlin housing websites.
 
 # Base classes and utilities
 from .base import (
+    BackoffActiveError,
     BaseScraper,
+    FilterCriteria,
     FlatDetails,
     HighTrafficError,
+    ParsingError,
     ScraperError,
     StandardFields,
     WebsiteUnavailableError,
     check_wbs_required,
+    compile_selectors,
+    detect_wbs,
+    drop_cross_source_duplicates,
+    extract_rent_details,
+    find_high_traffic_marker,
+    has_feature,
+    haversine_km,
+    normalize_address,
+    parse_available_date,
+    parse_cutoff_date,
+    parse_german_number,
+    parse_listing_date,
+    select_first,
 )
 
 # Cache management
 from .cache import (
+    evict_seen_flats,
     load_seen_flats,
     mark_flat_seen,
     mark_flats_as_seen,
@@ -24,10 +41,11 @@ from .cache import (
 )
 
 # Session management
-from .session import close_session, get_session
+from .session import close_session, configure_session, get_session
 
 # Individual scrapers
 from .degewo import DegewoScraper
+from .generic import GenericScraper
 from .gesobau import GesobauScraper
 from .gewobag import GewobagScraper
 from .inberlin import InBerlinWohnenScraper
@@ -35,14 +53,31 @@ from .stadtundland import StadtUndLandScraper
 
 __all__ = [
     # Base classes and utilities
+    "BackoffActiveError",
     "BaseScraper",
+    "FilterCriteria",
     "FlatDetails",
     "HighTrafficError",
+    "ParsingError",
     "ScraperError",
     "StandardFields",
     "WebsiteUnavailableError",
     "check_wbs_required",
+    "parse_german_number",
+    "parse_listing_date",
+    "parse_available_date",
+    "parse_cutoff_date",
+    "compile_selectors",
+    "detect_wbs",
+    "drop_cross_source_duplicates",
+    "extract_rent_details",
+    "find_high_traffic_marker",
+    "has_feature",
+    "haversine_km",
+    "normalize_address",
+    "select_first",
     # Cache management
+    "evict_seen_flats",
     "load_seen_flats",
     "mark_flat_seen",
     "mark_flats_as_seen",
@@ -50,9 +85,11 @@ __all__ = [
     "save_seen_flats",
     # Session management
     "close_session",
+    "configure_session",
     "get_session",
     # Scrapers
     "DegewoScraper",
+    "GenericScraper",
     "GesobauScraper",
     "GewobagScraper",
     "InBerlinWohnenScraper",
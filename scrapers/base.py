@@ -2,17 +2,55 @@
 
 import asyncio
 import gc
+import hashlib
 import logging
+import math
+import os
+import random
 import re
-from dataclasses import dataclass
-from datetime import datetime, timedelta
-from typing import Dict, List, Optional, Tuple
+from dataclasses import asdict, dataclass, field, fields
+from datetime import date, datetime, timedelta, timezone
+from typing import Dict, Iterable, List, Optional, Set, Tuple, Union
+from urllib.parse import parse_qsl, urlencode, urlsplit, urlunsplit
 
 import aiohttp
+import soupsieve
 from bs4 import BeautifulSoup
 
+from .districts import district_for_name, find_district_names
+from .session import request_headers, wait_for_request_slot
+
 logger = logging.getLogger(__name__)
 
+# Server errors that are usually transient and retried within _make_request
+RETRYABLE_STATUSES = {500, 502, 504}
+
+# Page texts that mean a website is overloaded or in maintenance instead of
+# listing flats, matched case-insensitively (HIGH_TRAFFIC_MARKERS overrides them)
+DEFAULT_HIGH_TRAFFIC_MARKERS = [
+    "high traffic",
+    "too many requests",
+    "hohe auslastung",
+    "hohes besucheraufkommen",
+    "erhöhtes besucheraufkommen",
+    "zu viele anfragen",
+    "vorübergehend nicht erreichbar",
+]
+
+# Query parameters that only track the visitor and don't identify the listing
+TRACKING_PARAMS = {
+    "fbclid",
+    "gclid",
+    "msclkid",
+    "mc_cid",
+    "mc_eid",
+    "_ga",
+    "sid",
+    "sessionid",
+    "jsessionid",
+    "phpsessid",
+}
+
 
 # Standard field names for FlatDetails.details dictionary
 # All scrapers should use these canonical field names
@@ -30,6 +68,7 @@ class StandardFields:
     PROVIDER = "provider"            # Housing company/provider (Anbieter)
     OBJECT_ID = "object_id"          # Object/apartment ID (Objekt-ID)
     FEATURES = "features"            # Special features/amenities (Tags/Besondere Eigenschaften)
+    FLOOR = "floor"                  # Floor (Etage/Geschoss), e.g. "EG", "3. OG"
 
 
 def check_wbs_required(text: str) -> bool:
@@ -45,8 +84,8 @@ def check_wbs_required(text: str) -> bool:
 
     text_lower = text.lower()
 
-    # Check if WBS is mentioned at all
-    if not re.search(r"\bwbs\b|wohnberechtigungsschein", text_lower):
+    # Check if WBS is mentioned at all ("mit Schein" is common shorthand)
+    if not re.search(r"\bwbs\b|wohnberechtigungsschein|\bmit schein\b", text_lower):
         return False  # No WBS mentioned = no WBS required
 
     # Patterns that indicate WBS is NOT required
@@ -59,6 +98,7 @@ def check_wbs_required(text: str) -> bool:
         "wbs nicht nötig",
         "ohne wohnberechtigungsschein",
         "kein wohnberechtigungsschein",
+        "ohne schein",
     ]
 
     for pattern in not_required_patterns:
@@ -73,6 +113,416 @@ def check_wbs_required(text: str) -> bool:
     return True
 
 
+def detect_wbs(
+    title: str, details: Dict[str, str], extra_texts: Iterable[str] = ()
+) -> bool:
+    """
+    Whether a listing requires a WBS, checking the title, every detail value
+    and any extra texts a scraper has (badges, raw API fields, element text).
+    """
+    sources = [title] + [str(value) for value in details.values() if value]
+    sources.extend(str(text) for text in extra_texts if text)
+    return any(check_wbs_required(source) for source in sources if source)
+
+
+def parse_german_number(text) -> Optional[float]:
+    """
+    Parse the first number in a German-formatted string.
+    Handles thousand separators and decimal commas, e.g. "800,00 €" -> 800.0,
+    "1.234 €" -> 1234.0, "1.234,56 €" -> 1234.56, "65,5 m²" -> 65.5.
+    A lone dot followed by exactly three digits is treated as a thousand separator.
+    """
+    if text is None:
+        return None
+    match = re.search(r"\d+(?:[.,]\d+)*", str(text))
+    if not match:
+        return None
+    number = match.group()
+    if "," in number:
+        number = number.replace(".", "").replace(",", ".")
+    elif re.fullmatch(r"\d{1,3}(?:\.\d{3})+", number):
+        number = number.replace(".", "")
+    try:
+        return float(number)
+    except ValueError:
+        return None
+
+
+# German labels of the rent components, as printed in listing texts
+RENT_LABELS = {
+    StandardFields.RENT_COLD: r"kaltmiete|nettokaltmiete|grundmiete",
+    StandardFields.RENT_ADDITIONAL: r"nebenkosten|betriebskosten",
+    StandardFields.RENT_HEATING: r"heizkosten",
+    StandardFields.RENT_WARM: r"warmmiete|gesamtmiete|bruttowarmmiete",
+}
+
+
+def extract_rent_details(text: str) -> Dict[str, str]:
+    """
+    Find labelled rent amounts in a listing text, e.g. "Kaltmiete: 650,00 €
+    Nebenkosten 120 €" -> {rent_cold: "650,00 €", rent_additional: "120 €"}.
+    """
+    found = {}
+    for field_name, labels in RENT_LABELS.items():
+        match = re.search(
+            rf"\b(?:{labels})\b\s*:?\s*(?:ca\.\s*)?(\d[\d.,]*)\s*(?:€|eur)",
+            text or "",
+            re.IGNORECASE,
+        )
+        if match:
+            found[field_name] = f"{match.group(1)} €"
+    return found
+
+
+def parse_floor(text) -> Optional[int]:
+    """
+    Parse a German floor description into a level: "EG"/"Erdgeschoss" -> 0,
+    "UG"/"Souterrain" -> -1, "2. UG" -> -2, "3. OG", "3. Etage" or "3" -> 3.
+    A bare "DG" (Dachgeschoss) gives None, as its level depends on the
+    building; with a number ("5. OG (DG)") the number is used. None if
+    unparseable.
+    """
+    if text is None or text == "":
+        return None
+    text = str(text).strip().lower()
+    match = re.search(r"-?\d+", text)
+    # Basement levels are counted downwards, checked before the number
+    if re.search(r"\b(ug|untergeschoss)\b", text):
+        return -abs(int(match.group())) if match else -1
+    if match:
+        return int(match.group())
+    if re.search(r"\b(eg|erdgeschoss|hochparterre|parterre)\b", text):
+        return 0
+    if re.search(r"\b(souterrain|tiefparterre)\b", text):
+        return -1
+    return None
+
+
+def find_high_traffic_marker(text: str, markers: Iterable[str]) -> Optional[str]:
+    """The first marker contained in text (case-insensitive), None if there is none."""
+    text = (text or "").lower()
+    return next((marker for marker in markers if marker.lower() in text), None)
+
+
+# Words naming a feature in titles or details, for the REQUIRE_* filters
+FEATURE_TERMS = {
+    "balcony": ("balkon", "terrasse", "loggia", "dachterrasse"),
+    "elevator": ("aufzug", "fahrstuhl", "personenaufzug", "lift"),
+}
+
+
+def has_feature(text: str, feature: str) -> bool:
+    """Whether text mentions the feature, ignoring negations like "kein Balkon"."""
+    text = (text or "").lower()
+    for term in FEATURE_TERMS[feature]:
+        for match in re.finditer(rf"\b{term}\w*", text):
+            before = text[max(0, match.start() - 6):match.start()]
+            if not re.search(r"\b(kein|keine|ohne|no)\s+$", before):
+                return True
+    return False
+
+
+def haversine_km(lat1: float, lon1: float, lat2: float, lon2: float) -> float:
+    """Great-circle distance between two points in kilometres."""
+    lat1, lon1, lat2, lon2 = map(math.radians, (lat1, lon1, lat2, lon2))
+    a = (
+        math.sin((lat2 - lat1) / 2) ** 2
+        + math.cos(lat1) * math.cos(lat2) * math.sin((lon2 - lon1) / 2) ** 2
+    )
+    return 2 * 6371.0 * math.asin(math.sqrt(a))
+
+
+def compile_selectors(selectors: List[str]) -> List[soupsieve.SoupSieve]:
+    """Compile CSS selectors once, e.g. at import time, instead of for every listing."""
+    return [soupsieve.compile(selector) for selector in selectors]
+
+
+def select_first(element, selectors: List[soupsieve.SoupSieve]):
+    """
+    Return the first element matching any of the given CSS selectors.
+    Selectors are tried in order, so the preferred one goes first and
+    fallbacks after it. This keeps a field working when a site renames
+    a class but an alternate selector still matches.
+    Selectors come from compile_selectors; plain strings are compiled on the fly.
+    """
+    for selector in selectors:
+        if isinstance(selector, str):
+            selector = soupsieve.compile(selector)
+        match = selector.select_one(element)
+        if match is not None:
+            return match
+    return None
+
+
+def parse_listing_date(value) -> Optional[datetime]:
+    """
+    Parse a listing timestamp as published by a housing website.
+    Accepts ISO 8601 strings (with or without timezone), German dates
+    (e.g. "01.03.2025") and Unix timestamps. Returns None if unparseable.
+    Timezone-aware values are converted to naive UTC so they stay comparable.
+    """
+    if value is None or value == "":
+        return None
+
+    parsed = None
+    if isinstance(value, (int, float)):
+        try:
+            parsed = datetime.fromtimestamp(value, tz=timezone.utc)
+        except (OverflowError, OSError, ValueError):
+            return None
+    else:
+        text = str(value).strip()
+        try:
+            parsed = datetime.fromisoformat(text.replace("Z", "+00:00"))
+        except ValueError:
+            for fmt in ("%d.%m.%Y %H:%M", "%d.%m.%Y"):
+                try:
+                    parsed = datetime.strptime(text, fmt)
+                    break
+                except ValueError:
+                    continue
+    if parsed is None:
+        return None
+
+    if parsed.tzinfo is not None:
+        parsed = parsed.astimezone(timezone.utc).replace(tzinfo=None)
+    return parsed
+
+
+GERMAN_MONTHS = {
+    "januar": 1, "jan": 1, "februar": 2, "feb": 2, "märz": 3, "maerz": 3, "mär": 3,
+    "april": 4, "apr": 4, "mai": 5, "juni": 6, "jun": 6, "juli": 7, "jul": 7,
+    "august": 8, "aug": 8, "september": 9, "sep": 9, "sept": 9, "oktober": 10,
+    "okt": 10, "november": 11, "nov": 11, "dezember": 12, "dez": 12,
+}
+
+
+def parse_available_date(text, today: Optional[date] = None) -> Optional[date]:
+    """
+    Parse an availability ("Verfügbar ab") value: "sofort" -> today,
+    "01.03.2025", "1.3.25", "2025-03-01", and month-only values such as
+    "03/2025" or "März 2025" (-> the 1st of the month). Surrounding words
+    ("ab 01.03.2025") are ignored. None if unparseable, e.g. "nach Vereinbarung".
+    """
+    if text is None or text == "":
+        return None
+    text = str(text).strip().lower()
+    if "sofort" in text:
+        return today or date.today()
+
+    try:
+        match = re.search(r"\b(\d{4})-(\d{2})-(\d{2})\b", text)
+        if match:
+            return date(int(match.group(1)), int(match.group(2)), int(match.group(3)))
+        match = re.search(r"\b(\d{1,2})\.(\d{1,2})\.(\d{4}|\d{2})\b", text)
+        if match:
+            year = int(match.group(3))
+            return date(year if year > 99 else 2000 + year, int(match.group(2)), int(match.group(1)))
+        match = re.search(r"\b(\d{1,2})[./](\d{4})\b", text)
+        if match:
+            return date(int(match.group(2)), int(match.group(1)), 1)
+    except ValueError:
+        return None
+    match = re.search(r"([a-zä]+)\.?\s+(\d{4})\b", text)
+    if match and match.group(1) in GERMAN_MONTHS:
+        return date(int(match.group(2)), GERMAN_MONTHS[match.group(1)], 1)
+    return None
+
+
+def decode_html(body: bytes, charset: Optional[str] = None) -> str:
+    """
+    Decode a response body. Uses the Content-Type charset, else a <meta charset>
+    declaration, else UTF-8. Bytes that aren't valid in that encoding (e.g. a
+    site declaring UTF-8 but serving ISO-8859-1) are decoded as Windows-1252,
+    a superset of Latin-1, so German umlauts survive.
+    """
+    if not charset:
+        match = re.search(rb"<meta[^>]+charset=[\"']?([\w-]+)", body[:2048], re.IGNORECASE)
+        charset = match.group(1).decode("ascii") if match else "utf-8"
+    try:
+        return body.decode(charset)
+    except (LookupError, UnicodeDecodeError):
+        pass
+    try:
+        return body.decode("utf-8")
+    except UnicodeDecodeError:
+        return body.decode("cp1252", errors="replace")
+
+
+def canonicalize_link(link: Optional[str]) -> Optional[str]:
+    """
+    Strip tracking query parameters (utm_*, fbclid, session ids, ...),
+    ";jsessionid=" path parameters and trailing slashes, so the same listing
+    keeps the same link when a website changes them between requests.
+    """
+    if not link:
+        return link
+    parts = urlsplit(link.strip())
+    path = re.sub(r";jsessionid=[^/]*", "", parts.path, flags=re.IGNORECASE).rstrip("/")
+    query = [
+        (key, value)
+        for key, value in parse_qsl(parts.query, keep_blank_values=True)
+        if not key.lower().startswith("utm_") and key.lower() not in TRACKING_PARAMS
+    ]
+    return urlunsplit(
+        (parts.scheme, parts.netloc, path, urlencode(query, doseq=True), parts.fragment)
+    )
+
+
+def normalize_address(address: Optional[str]) -> Optional[str]:
+    """
+    Street and house number in a comparable form, e.g. "Müllerstraße 12 A,
+    13353 Berlin" and "Müllerstr. 12a" -> "müllerstr 12a". None without a
+    house number, as a street alone doesn't identify a flat.
+    """
+    text = (address or "").lower().replace("ß", "ss")
+    text = re.sub(r"(strasse|str\.?)(?=[\s\d,]|$)", "str ", text)
+    match = re.search(r"([a-zäöü][a-zäöü.\- ]*?)\s*(\d+)\s*([a-z])?\b", text)
+    if not match:
+        return None
+    street = re.sub(r"[^a-zäöü]", "", match.group(1))
+    return f"{street} {match.group(2)}{match.group(3) or ''}"
+
+
+@dataclass
+class FilterCriteria:
+    """Filters a flat has to pass before users are notified about it."""
+
+    min_rooms: float = 2.0
+    max_rooms: Optional[float] = None
+    allow_wbs: bool = False
+    require_balcony: bool = False
+    require_elevator: bool = False
+    max_rent: Optional[float] = None
+    min_area: Optional[float] = None
+    districts: List[str] = field(default_factory=list)
+    exclude_keywords: List[str] = field(default_factory=list)
+    include_keywords: List[str] = field(default_factory=list)
+    min_floor: Optional[int] = None
+    max_floor: Optional[int] = None
+    # Highest floor accepted when no elevator is mentioned, for limited mobility
+    max_floor_without_elevator: Optional[int] = None
+    # Latest acceptable move-in date
+    available_before: Optional[date] = None
+    # Maximum distance in km from the center point, flats are geocoded for it
+    center_lat: Optional[float] = None
+    center_lon: Optional[float] = None
+    max_distance_km: Optional[float] = None
+
+    def describe(self) -> str:
+        """Short human-readable summary of the active filters."""
+        parts = [f"{self.describe_rooms()} rooms"]
+        if self.min_area is not None:
+            parts.append(f"{self.min_area:g}+ m²")
+        if self.max_rent is not None:
+            parts.append(f"max {self.max_rent:g} € warm")
+        if self.min_floor is not None or self.max_floor is not None:
+            low = "" if self.min_floor is None else self.min_floor
+            high = "" if self.max_floor is None else self.max_floor
+            parts.append(f"floor {low}–{high}")
+        if self.max_floor_without_elevator is not None:
+            parts.append(f"above floor {self.max_floor_without_elevator} only with elevator")
+        if self.available_before is not None:
+            parts.append(f"available by {self.available_before:%d.%m.%Y}")
+        if self.uses_distance():
+            parts.append(f"within {self.max_distance_km:g} km")
+        parts.append("WBS allowed" if self.allow_wbs else "No WBS required")
+        if self.require_balcony:
+            parts.append("with balcony")
+        if self.require_elevator:
+            parts.append("with elevator")
+        if self.districts:
+            parts.append(", ".join(self.districts))
+        if self.include_keywords:
+            parts.append(f"with {' / '.join(self.include_keywords)}")
+        if self.exclude_keywords:
+            parts.append(f"without {' / '.join(self.exclude_keywords)}")
+        return " • ".join(parts)
+
+    def uses_distance(self) -> bool:
+        """Whether the distance filter is complete, so flats need to be geocoded."""
+        return None not in (self.center_lat, self.center_lon, self.max_distance_km)
+
+    def describe_rooms(self) -> str:
+        """The allowed room counts, e.g. "2+" or "1–3"."""
+        if self.max_rooms is None:
+            return f"{self.min_rooms:g}+"
+        return f"{self.min_rooms:g}–{self.max_rooms:g}"
+
+    def set_value(self, name: str, value: str):
+        """Update a single filter from a text value, e.g. ("min_rooms", "3").
+
+        Optional filters are cleared with "none". Raises ValueError on bad input.
+        """
+        known = {f.name for f in fields(self)}
+        if name not in known:
+            raise ValueError(f"Unknown filter '{name}'. Known filters: {', '.join(sorted(known))}")
+
+        value = value.strip()
+        clear = value.lower() in ("none", "off", "-", "")
+        if name in ("allow_wbs", "require_balcony", "require_elevator"):
+            if value.lower() in ("true", "yes", "on", "1"):
+                setattr(self, name, True)
+            elif value.lower() in ("false", "no", "off", "0"):
+                setattr(self, name, False)
+            else:
+                raise ValueError(f"{name} must be true or false")
+        elif name in ("districts", "exclude_keywords", "include_keywords"):
+            values = [] if clear else [v.strip() for v in value.split(",") if v.strip()]
+            setattr(self, name, values)
+        elif name == "min_rooms":
+            self.min_rooms = 0.0 if clear else _parse_non_negative(name, value)
+        elif name in ("min_floor", "max_floor", "max_floor_without_elevator"):
+            try:
+                setattr(self, name, None if clear else int(value))
+            except ValueError:
+                raise ValueError(f"{name} must be a whole number (0 = ground floor)")
+        elif clear:
+            setattr(self, name, None)
+        elif name == "available_before":
+            self.available_before = parse_cutoff_date(name, value)
+        elif name in ("center_lat", "center_lon"):
+            try:
+                setattr(self, name, float(value.replace(",", ".")))
+            except ValueError:
+                raise ValueError(f"{name} must be a coordinate like 52.52, got '{value}'")
+        else:
+            setattr(self, name, _parse_non_negative(name, value))
+
+    def to_dict(self) -> Dict:
+        """JSON-serializable representation, see from_dict()."""
+        data = asdict(self)
+        if self.available_before is not None:
+            data["available_before"] = self.available_before.isoformat()
+        return data
+
+    @classmethod
+    def from_dict(cls, data: Dict) -> "FilterCriteria":
+        known = {f.name for f in fields(cls)}
+        values = {k: v for k, v in data.items() if k in known}
+        if values.get("available_before"):
+            values["available_before"] = date.fromisoformat(values["available_before"])
+        return cls(**values)
+
+
+def parse_cutoff_date(name: str, value) -> date:
+    """Parse a date filter value such as "01.03.2025" or "2025-03-01"."""
+    parsed = None if "sofort" in str(value).lower() else parse_available_date(value)
+    if parsed is None:
+        raise ValueError(f"{name} must be a date like 01.03.2025, got '{value}'")
+    return parsed
+
+
+def _parse_non_negative(name: str, value: str) -> float:
+    try:
+        number = float(value.replace(",", "."))
+    except ValueError:
+        raise ValueError(f"{name} must be a number, got '{value}'")
+    if number < 0:
+        raise ValueError(f"{name} must not be negative")
+    return number
+
+
 @dataclass
 class FlatDetails:
     id: str
@@ -81,11 +531,200 @@ class FlatDetails:
     details: Dict[str, str]
     wbs_required: bool
     source: str
+    # When the listing was published, if the website exposes it
+    listed_at: Optional[datetime] = None
+    # (latitude, longitude) of the address, set by the geocoder if enabled
+    location: Optional[Tuple[float, float]] = None
 
     def __post_init__(self):
         # Convert details to a regular dictionary if it's a tuple
         if isinstance(self.details, tuple):
             self.details = dict(self.details)
+        # JSON turns the location tuple into a list
+        if self.location is not None:
+            self.location = tuple(self.location)
+
+    def to_dict(self) -> Dict:
+        """JSON-serializable representation, see from_dict()."""
+        data = asdict(self)
+        data["listed_at"] = self.listed_at.isoformat() if self.listed_at else None
+        return data
+
+    @classmethod
+    def from_dict(cls, data: Dict) -> "FlatDetails":
+        known = {f.name for f in fields(cls)}
+        values = {k: v for k, v in data.items() if k in known}
+        values["listed_at"] = parse_listing_date(values.get("listed_at"))
+        return cls(**values)
+
+    def room_count(self) -> Optional[float]:
+        """Parse the room count from details, e.g. "2", "2,5 Zimmer". None if unknown."""
+        rooms = self.details.get(StandardFields.ROOMS)
+        if not rooms:
+            return None
+        match = re.search(r"\d+(?:[.,]\d+)?", str(rooms))
+        if not match:
+            return None
+        return float(match.group().replace(",", "."))
+
+    def warm_rent(self) -> Optional[float]:
+        """Parse the warm rent in euros from details, falling back to the total rent."""
+        for field in (StandardFields.RENT_WARM, StandardFields.RENT_TOTAL):
+            rent = parse_german_number(self.details.get(field))
+            if rent:
+                return rent
+        return None
+
+    def total_rent(self) -> Optional[float]:
+        """The monthly rent to budget for: the warm rent if given, else Kaltmiete
+        plus Nebenkosten (and Heizkosten, if listed separately). None if unknown."""
+        warm = self.warm_rent()
+        if warm is not None:
+            return warm
+        cold = parse_german_number(self.details.get(StandardFields.RENT_COLD))
+        additional = parse_german_number(self.details.get(StandardFields.RENT_ADDITIONAL))
+        if not cold or not additional:
+            return None
+        heating = parse_german_number(self.details.get(StandardFields.RENT_HEATING)) or 0.0
+        return cold + additional + heating
+
+    def floor(self) -> Optional[int]:
+        """The floor from details ("EG" = 0), None if unknown."""
+        return parse_floor(self.details.get(StandardFields.FLOOR))
+
+    def available_from(self) -> Optional[date]:
+        """The move-in date from details ("sofort" = today), None if unknown."""
+        return parse_available_date(self.details.get(StandardFields.AVAILABLE_FROM))
+
+    def distance_km(self, lat: float, lon: float) -> Optional[float]:
+        """Distance from the given point, None if the flat wasn't geocoded."""
+        if self.location is None:
+            return None
+        return haversine_km(self.location[0], self.location[1], lat, lon)
+
+    def area_sqm(self) -> Optional[float]:
+        """Parse the living area in m² from details, e.g. "65,5 m²" or "70 m2"."""
+        return parse_german_number(self.details.get(StandardFields.AREA)) or None
+
+    def district_names(self) -> Set[str]:
+        """Known Berlin Bezirk/Ortsteil names found in the district or address details."""
+        names = find_district_names(self.details.get(StandardFields.DISTRICT))
+        if names:
+            return names
+
+        address = self.details.get(StandardFields.ADDRESS) or ""
+        # The locality usually follows the postal code ("..., 13353 Wedding")
+        after_postcode = re.split(r"\b\d{5}\b", address, maxsplit=1)
+        if len(after_postcode) == 2:
+            names = find_district_names(after_postcode[1])
+        return names or find_district_names(address)
+
+    def detected_district(self) -> Optional[str]:
+        """Derive the Bezirk from the district or address details, if recognizable."""
+        for name in sorted(self.district_names()):
+            district = district_for_name(name)
+            if district == name:
+                return district
+        return None
+
+    def meets_criteria(self, criteria: FilterCriteria) -> bool:
+        """Check if the flat matches the given filters.
+
+        Flats whose room count, rent, area, floor, availability date or location
+        can't be determined are accepted. Required features (balcony, elevator)
+        must be mentioned in the title or details; so must an elevator for flats
+        above max_floor_without_elevator.
+        With a district allowlist, flats without a recognizable district are rejected.
+        """
+        rooms = self.room_count()
+        if rooms is not None and rooms < criteria.min_rooms:
+            return False
+        if rooms is not None and criteria.max_rooms is not None and rooms > criteria.max_rooms:
+            return False
+        if self.wbs_required and not criteria.allow_wbs:
+            return False
+        if criteria.max_rent is not None:
+            rent = self.total_rent()
+            if rent is not None and rent > criteria.max_rent:
+                return False
+        if criteria.min_area is not None:
+            area = self.area_sqm()
+            if area is not None and area < criteria.min_area:
+                return False
+        if criteria.min_floor is not None or criteria.max_floor is not None:
+            floor = self.floor()
+            if floor is not None:
+                if criteria.min_floor is not None and floor < criteria.min_floor:
+                    return False
+                if criteria.max_floor is not None and floor > criteria.max_floor:
+                    return False
+        if criteria.max_floor_without_elevator is not None:
+            floor = self.floor()
+            if (
+                floor is not None
+                and floor > criteria.max_floor_without_elevator
+                and not has_feature(self.searchable_text(), "elevator")
+            ):
+                return False
+        if criteria.available_before is not None:
+            available = self.available_from()
+            if available is not None and available > criteria.available_before:
+                return False
+        if criteria.require_balcony or criteria.require_elevator:
+            text = self.searchable_text()
+            if criteria.require_balcony and not has_feature(text, "balcony"):
+                return False
+            if criteria.require_elevator and not has_feature(text, "elevator"):
+                return False
+        if criteria.uses_distance():
+            distance = self.distance_km(criteria.center_lat, criteria.center_lon)
+            if distance is not None and distance > criteria.max_distance_km:
+                return False
+        if criteria.districts:
+            allowed = {name.strip().lower() for name in criteria.districts}
+            found = {name.lower() for name in self.district_names()}
+            if not allowed & found:
+                return False
+        if criteria.exclude_keywords or criteria.include_keywords:
+            text = self.searchable_text()
+            if any(keyword.lower() in text for keyword in criteria.exclude_keywords):
+                return False
+            if criteria.include_keywords and not any(
+                keyword.lower() in text for keyword in criteria.include_keywords
+            ):
+                return False
+        return True
+
+    def searchable_text(self) -> str:
+        """Lowercased title and detail values, for keyword filters."""
+        parts = [self.title or ""] + [str(v) for v in self.details.values() if v]
+        return " ".join(parts).lower()
+
+    def canonical_link(self) -> Optional[str]:
+        """The link without tracking parameters; self.link keeps the original."""
+        return canonicalize_link(self.link)
+
+    def content_hash(self) -> str:
+        """Stable hash of title, canonical link and details, to recognize re-published listings.
+
+        Details are sorted, so the order in which a scraper fills them doesn't matter.
+        """
+        parts = [self.title or "", self.canonical_link() or ""]
+        parts.extend(f"{key}={value}" for key, value in sorted(self.details.items()))
+        return hashlib.sha1("\n".join(parts).encode("utf-8")).hexdigest()[:16]
+
+    def dedup_key(self) -> Optional[str]:
+        """
+        Address, room count and total rent, to recognize the same flat listed on
+        several websites under different IDs. None if any of them is unknown;
+        such flats are never treated as duplicates.
+        """
+        address = normalize_address(self.details.get(StandardFields.ADDRESS))
+        rooms = self.room_count()
+        rent = self.total_rent()
+        if address is None or rooms is None or rent is None:
+            return None
+        return f"{address}|{rooms:g}|{round(rent)}"
 
     def is_duplicate(self) -> bool:
         """Check if this flat has been seen before."""
@@ -97,6 +736,33 @@ class FlatDetails:
         return False
 
 
+def drop_cross_source_duplicates(
+    flats: List[FlatDetails], sent: Optional[Dict[str, Set[str]]] = None
+) -> List[FlatDetails]:
+    """
+    Drop flats with the same dedup_key() as an earlier flat from another
+    source, keeping the first one. Flats of the same source are always kept,
+    as one website may list several equal flats in a building.
+
+    sent maps dedup keys of flats notified in earlier checks to their
+    sources; flats matching them from other sources are dropped as well.
+    """
+    sources_by_key: Dict[str, Set[str]] = {
+        key: set(sources) for key, sources in (sent or {}).items()
+    }
+    unique = []
+    for flat in flats:
+        key = flat.dedup_key()
+        if key is not None:
+            sources = sources_by_key.setdefault(key, set())
+            if sources - {flat.source}:
+                logger.info(f"Skipping {flat.source} flat {flat.id}, also listed on {', '.join(sorted(sources))}")
+                continue
+            sources.add(flat.source)
+        unique.append(flat)
+    return unique
+
+
 class ScraperError(Exception):
     """Base exception for scraper errors"""
     pass
@@ -107,27 +773,135 @@ class WebsiteUnavailableError(ScraperError):
     pass
 
 
+class BackoffActiveError(WebsiteUnavailableError):
+    """Raised when a request is skipped because the scraper is backing off"""
+    pass
+
+
 class HighTrafficError(ScraperError):
     """Raised when a website is experiencing high traffic"""
     pass
 
 
+class ParsingError(ScraperError):
+    """Raised when too many listings on a page could not be parsed"""
+    pass
+
+
+# Weight of the latest fetch in the reliability score, see BaseScraper.record_result
+RELIABILITY_SMOOTHING = 0.2
+
+
 class BaseScraper:
-    def __init__(self, url: str):
-        self.url = url
+    def __init__(self, url: Union[str, List[str]]):
+        # Search result pages; fetch_all fetches each of them
+        self.urls: List[str] = [url] if isinstance(url, str) else list(url)
+        # The first search URL, fetched when fetch_flats is given none
+        self.url = self.urls[0]
         self.last_error_time: Optional[datetime] = None
         self.error_count: int = 0
-        self.backoff_time: int = 60
+        self.base_backoff_time: int = 60
+        self.backoff_time: int = self.base_backoff_time
         self.max_backoff_time: int = 3600
         self.max_retries: int = 3
+        # Texts identifying an overload or maintenance page, see check_high_traffic
+        self.high_traffic_markers: List[str] = list(DEFAULT_HIGH_TRAFFIC_MARKERS)
+        # Fraction of matched listings allowed to fail parsing (None = never fail)
+        self.parse_failure_threshold: Optional[float] = None
+        # Result pages fetched per check, for scrapers that paginate
+        self.max_pages: int = 3
+        # How long the last fetch took, shown in /status
+        self.last_duration_ms: Optional[int] = None
+        # HTTP status of the last response and flats found by the last
+        # successful fetch_all, for the startup self-test
+        self.last_status: Optional[int] = None
+        self.last_item_count: Optional[int] = None
+        # Moving average of fetch outcomes (1 = success), None before the first fetch
+        self.success_rate: Optional[float] = None
+        # Random source for retry jitter, seed it for reproducible delays
+        self.rng = random.Random()
+        # Directory to save every fetched page to (None = don't save)
+        self.debug_dir: Optional[str] = None
+        # Snapshot of the page fetched last, logged when it yielded no flats
+        self.last_snapshot_path: Optional[str] = None
+        # Headers and cookies added to every request, e.g. a consent cookie
+        # for a cookie wall (SCRAPER_HEADERS / SCRAPER_COOKIES)
+        self.extra_headers: Dict[str, str] = {}
+        self.cookies: Dict[str, str] = {}
         self._parser = (
             "html.parser"  # Use html.parser instead of lxml for lower memory usage
         )
 
-    async def fetch_flats(self) -> List[FlatDetails]:
-        """Base method to fetch flats from a website."""
+    @property
+    def name(self) -> str:
+        """Name used for status reporting and command arguments."""
+        return self.__class__.__name__
+
+    def matches_name(self, name: str) -> bool:
+        """Check if a user-supplied name refers to this scraper.
+
+        Matching is case-insensitive, ignores spaces and accepts the name
+        with or without the "Scraper" suffix (e.g. "degewo", "DegewoScraper").
+        """
+        wanted = name.replace(" ", "").lower()
+        own = self.name.lower()
+        return wanted in (own, own.removesuffix("scraper"))
+
+    async def fetch_flats(self, url: Optional[str] = None) -> List[FlatDetails]:
+        """Base method to fetch flats from a website (from url, default self.url)."""
         raise NotImplementedError("Subclasses must implement fetch_flats")
 
+    # Whether the search pages can be replaced with SCRAPER_URLS
+    supports_urls = True
+
+    def set_urls(self, urls: List[str]):
+        """Replace the search URLs, e.g. with separate pages per district."""
+        self.urls = list(urls)
+        self.url = self.urls[0]
+
+    async def fetch_all(self) -> List[FlatDetails]:
+        """Fetch flats from every search URL and merge them, dropping duplicate IDs.
+
+        An error on any URL fails the whole fetch, like it does for a single
+        URL. Once an error put the scraper in backoff, the remaining URLs
+        are not requested.
+        """
+        flats = []
+        self.last_snapshot_path = None
+        for url in self.urls:
+            flats.extend(await self.fetch_flats(url))
+        flats = self._filter_duplicates(flats)
+        self.last_item_count = len(flats)
+        return flats
+
+    def check_high_traffic(self, soup: BeautifulSoup):
+        """Raise HighTrafficError if the page shows an overload or maintenance message."""
+        marker = find_high_traffic_marker(soup.get_text(" "), self.high_traffic_markers)
+        if marker:
+            raise HighTrafficError(f"Website experiencing high traffic (page says '{marker}')")
+
+    def _request_headers(self, headers: Optional[Dict[str, str]] = None) -> Dict[str, str]:
+        """Headers for one request: the given ones, overridden by the configured
+        extra headers, plus a Cookie header with the configured cookies."""
+        merged = {**(headers or {}), **self.extra_headers}
+        if self.cookies:
+            merged["Cookie"] = "; ".join(f"{name}={value}" for name, value in self.cookies.items())
+        return request_headers(merged)
+
+    def _save_snapshot(self, text: str, extension: str = "html"):
+        """Write a fetched page to debug_dir, if set, to debug the parsers later."""
+        if not self.debug_dir:
+            return
+        timestamp = datetime.now().strftime("%Y%m%dT%H%M%S%f")
+        path = os.path.join(self.debug_dir, f"{self.name}-{timestamp}.{extension}")
+        try:
+            os.makedirs(self.debug_dir, exist_ok=True)
+            with open(path, "w", encoding="utf-8") as f:
+                f.write(text)
+            self.last_snapshot_path = path
+        except OSError as e:
+            logger.warning(f"{self.name}: could not save page snapshot to {path}: {e}")
+
     def _check_backoff(self) -> bool:
         """Check if we should back off from making requests."""
         if self.last_error_time is None:
@@ -138,6 +912,18 @@ class BaseScraper:
             return True
         return False
 
+    def backoff_remaining(self) -> Optional[timedelta]:
+        """Time until requests are attempted again, None if not backing off."""
+        if self.last_error_time is None:
+            return None
+        remaining = self.last_error_time + timedelta(seconds=self.backoff_time) - datetime.now()
+        return remaining if remaining > timedelta(0) else None
+
+    @property
+    def in_backoff(self) -> bool:
+        """Whether requests are currently skipped because of earlier errors."""
+        return self._check_backoff()
+
     def _update_backoff(self):
         """Update backoff time based on error count."""
         self.error_count += 1
@@ -147,28 +933,91 @@ class BaseScraper:
     def _reset_backoff(self):
         """Reset backoff time after successful request."""
         self.error_count = 0
-        self.backoff_time = 60
+        self.backoff_time = self.base_backoff_time
         self.last_error_time = None
 
+    def record_result(self, success: bool):
+        """Fold a fetch outcome into the success rate (an exponential moving average).
+
+        Recent fetches weigh most, so a site that failed once recovers after a
+        few good fetches, while one that keeps failing drops towards 0%.
+        """
+        outcome = 1.0 if success else 0.0
+        if self.success_rate is None:
+            self.success_rate = outcome
+        else:
+            self.success_rate += RELIABILITY_SMOOTHING * (outcome - self.success_rate)
+
+    def reliability(self) -> Optional[int]:
+        """Success rate as a 0–100% score, None if the site wasn't fetched yet."""
+        if self.success_rate is None:
+            return None
+        return round(self.success_rate * 100)
+
+    def configure_retries(
+        self,
+        max_retries: Optional[int] = None,
+        base_backoff: Optional[int] = None,
+        max_backoff: Optional[int] = None,
+    ):
+        """Override the retry defaults; values left as None keep the current setting."""
+        if max_retries is not None:
+            self.max_retries = max(1, max_retries)
+        if base_backoff is not None:
+            self.base_backoff_time = base_backoff
+            self.backoff_time = base_backoff
+        if max_backoff is not None:
+            self.max_backoff_time = max_backoff
+
+    def clear_backoff(self):
+        """Force-expire the backoff so the next cycle retries immediately."""
+        self._reset_backoff()
+
+    def _retry_delay(self, attempt: int) -> float:
+        """Exponential delay with full jitter, so scrapers don't retry in lockstep."""
+        return self.rng.uniform(0, min(2**attempt, self.max_backoff_time))
+
     async def _make_request(
-        self, session: aiohttp.ClientSession, method: str = "GET", **kwargs
+        self,
+        session: aiohttp.ClientSession,
+        method: str = "GET",
+        url: Optional[str] = None,
+        snapshot_extension: str = "html",
+        **kwargs,
     ) -> Tuple[bool, str]:
+        """Request self.url (or the given url), retrying transient failures.
+
+        Other keyword arguments go to session.request, e.g. json for a POST body.
+        """
         if self._check_backoff():
-            raise WebsiteUnavailableError(
+            raise BackoffActiveError(
                 f"Website is in backoff period. Retry in {self.backoff_time} seconds."
             )
 
+        kwargs["headers"] = self._request_headers(kwargs.get("headers"))
         for attempt in range(self.max_retries):
+            await wait_for_request_slot()
             try:
-                async with session.request(method, self.url, **kwargs) as response:
+                async with session.request(method, url or self.url, **kwargs) as response:
+                    self.last_status = response.status
                     if response.status == 200:
                         self._reset_backoff()
-                        return True, await response.text()
+                        html = decode_html(await response.read(), response.charset)
+                        self._save_snapshot(html, snapshot_extension)
+                        return True, html
                     elif response.status == 503 or response.status == 429:
                         self._update_backoff()
                         raise HighTrafficError(
                             f"Website experiencing high traffic. Status: {response.status}"
                         )
+                    elif (
+                        response.status in RETRYABLE_STATUSES
+                        and attempt < self.max_retries - 1
+                    ):
+                        logger.warning(
+                            f"{self.name}: status {response.status}, retrying "
+                            f"(attempt {attempt + 1}/{self.max_retries})"
+                        )
                     else:
                         self._update_backoff()
                         raise WebsiteUnavailableError(
@@ -178,15 +1027,29 @@ class BaseScraper:
                 if attempt == self.max_retries - 1:
                     self._update_backoff()
                     raise WebsiteUnavailableError("Request timed out")
-                await asyncio.sleep(2**attempt)
+                await asyncio.sleep(self._retry_delay(attempt))
             except aiohttp.ClientError as e:
                 if attempt == self.max_retries - 1:
                     self._update_backoff()
                     raise WebsiteUnavailableError(f"Connection error: {str(e)}")
-                await asyncio.sleep(2**attempt)
+                await asyncio.sleep(self._retry_delay(attempt))
+            else:
+                # Retryable status, the response is released before waiting
+                await asyncio.sleep(self._retry_delay(attempt))
 
         return False, ""
 
+    def _check_parse_failures(self, matched: int, parsed: int):
+        """Raise ParsingError if the share of unparseable listings exceeds the threshold."""
+        if self.parse_failure_threshold is None or matched == 0:
+            return
+        failure_ratio = (matched - parsed) / matched
+        if failure_ratio > self.parse_failure_threshold:
+            raise ParsingError(
+                f"Parsed only {parsed} of {matched} listings "
+                f"({failure_ratio:.0%} failed, threshold {self.parse_failure_threshold:.0%})"
+            )
+
     def _parse_html(self, html: str) -> BeautifulSoup:
         # Use html.parser for lower memory usage
         return BeautifulSoup(html, self._parser)
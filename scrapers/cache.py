@@ -2,16 +2,20 @@
 
 import json
 import logging
+import time
 from pathlib import Path
-from typing import TYPE_CHECKING, List, Set
+from typing import TYPE_CHECKING, Dict, List, Optional, Set
 
 if TYPE_CHECKING:
     from .base import FlatDetails
 
 logger = logging.getLogger(__name__)
 
-# Global set to track seen flat IDs
-_seen_flat_ids: Set[str] = set()
+# Global map of seen flat IDs to when they were first seen (Unix time)
+_seen_flat_ids: Dict[str, float] = {}
+# Dedup keys of notified flats, mapped to their sources and when each source
+# was first notified (Unix time), to skip the same flat on another website later
+_sent_dedup_keys: Dict[str, Dict[str, float]] = {}
 # Cache file for persisting seen flat IDs (in /dev/shm which is guaranteed RAM disk)
 _SEEN_FLATS_CACHE_FILE = "/dev/shm/seen_flats_cache.json"
 # Track if cache has been modified since last save
@@ -22,29 +26,41 @@ _CACHE_WRITE_THRESHOLD = 10  # Write to disk every 10 new flats
 
 
 def load_seen_flats():
-    """Load seen flat IDs from cache file in RAM disk (/dev/shm)."""
-    global _seen_flat_ids, _cache_modified, _cache_write_counter
+    """Load seen flat IDs and sent dedup keys from cache file in RAM disk (/dev/shm)."""
+    global _seen_flat_ids, _sent_dedup_keys, _cache_modified, _cache_write_counter
     cache_file = Path(_SEEN_FLATS_CACHE_FILE)
+    _sent_dedup_keys = {}
     if cache_file.exists():
         try:
             with open(cache_file, "r") as f:
                 data = json.load(f)
-                # Handle both old format {"seen_ids": [...]} and new format [...]
-                if isinstance(data, dict):
-                    _seen_flat_ids = set(data.get("seen_ids", []))
+                # Handle the old formats {"seen_ids": [...]} and [...] without
+                # timestamps (counted as seen now), {id: time} without dedup
+                # keys and the new format {"seen": {id: time}, "dedup_keys": ...}
+                if isinstance(data, dict) and "seen" in data:
+                    _seen_flat_ids = {flat_id: float(seen) for flat_id, seen in data["seen"].items()}
+                    _sent_dedup_keys = {
+                        key: {source: float(sent) for source, sent in sources.items()}
+                        for key, sources in data.get("dedup_keys", {}).items()
+                    }
+                elif isinstance(data, dict) and "seen_ids" in data:
+                    _seen_flat_ids = dict.fromkeys(data["seen_ids"], time.time())
+                elif isinstance(data, list):
+                    _seen_flat_ids = dict.fromkeys(data, time.time())
                 else:
-                    _seen_flat_ids = set(data)
+                    _seen_flat_ids = {flat_id: float(seen) for flat_id, seen in data.items()}
                 logger.info(f"Loaded {len(_seen_flat_ids)} seen flat IDs from RAM cache")
                 _cache_modified = False
                 _cache_write_counter = 0
-        except (json.JSONDecodeError, IOError) as e:
+        except (json.JSONDecodeError, IOError, ValueError, AttributeError) as e:
             logger.error(f"Failed to load seen flats cache: {e}")
-            _seen_flat_ids = set()
+            _seen_flat_ids = {}
+            _sent_dedup_keys = {}
             _cache_modified = False
             _cache_write_counter = 0
     else:
         logger.info("No seen flats cache file found, starting fresh")
-        _seen_flat_ids = set()
+        _seen_flat_ids = {}
         _cache_modified = False
         _cache_write_counter = 0
 
@@ -70,7 +86,9 @@ def save_seen_flats(force: bool = False):
         cache_file = Path(_SEEN_FLATS_CACHE_FILE)
         # Write compact JSON (no spaces/indentation) to minimize size
         with open(cache_file, "w") as f:
-            json.dump(list(_seen_flat_ids), f, separators=(',', ':'))
+            json.dump(
+                {"seen": _seen_flat_ids, "dedup_keys": _sent_dedup_keys}, f, separators=(',', ':')
+            )
         logger.info(f"Saved {len(_seen_flat_ids)} seen flat IDs to RAM cache")
         _cache_modified = False
         _cache_write_counter = 0
@@ -79,9 +97,10 @@ def save_seen_flats(force: bool = False):
 
 
 def reset_seen_flats():
-    """Reset the set of seen flat IDs and delete cache file."""
+    """Reset the set of seen flat IDs and sent dedup keys and delete cache file."""
     global _seen_flat_ids, _cache_modified, _cache_write_counter
     _seen_flat_ids.clear()
+    _sent_dedup_keys.clear()
     _cache_modified = False
     _cache_write_counter = 0
     cache_file = Path(_SEEN_FLATS_CACHE_FILE)
@@ -94,20 +113,27 @@ def reset_seen_flats():
 
 
 def mark_flats_as_seen(flats: List['FlatDetails']):
-    """Mark a list of flats as seen in the global cache."""
+    """Mark a list of flats as seen in the global cache, with their dedup keys."""
     global _seen_flat_ids, _cache_modified, _cache_write_counter
+    now = time.time()
     for flat in flats:
         if flat.id not in _seen_flat_ids:
-            _seen_flat_ids.add(flat.id)
+            _seen_flat_ids[flat.id] = now
             _cache_modified = True
             _cache_write_counter += 1
+        key = flat.dedup_key()
+        if key is not None:
+            sources = _sent_dedup_keys.setdefault(key, {})
+            if flat.source not in sources:
+                sources[flat.source] = now
+                _cache_modified = True
 
 
 def mark_flat_seen(flat_id: str):
     """Mark a single flat ID as seen."""
     global _seen_flat_ids, _cache_modified, _cache_write_counter
     if flat_id not in _seen_flat_ids:
-        _seen_flat_ids.add(flat_id)
+        _seen_flat_ids[flat_id] = time.time()
         _cache_modified = True
         _cache_write_counter += 1
 
@@ -115,3 +141,31 @@ def mark_flat_seen(flat_id: str):
 def is_flat_seen(flat_id: str) -> bool:
     """Check if a flat ID has been seen before."""
     return flat_id in _seen_flat_ids
+
+
+def sent_dedup_sources(keys: List[str]) -> Dict[str, Set[str]]:
+    """Sources of notified flats with these dedup keys, by key."""
+    return {key: set(_sent_dedup_keys[key]) for key in keys if key in _sent_dedup_keys}
+
+
+def evict_seen_flats(max_age_seconds: float, now: Optional[float] = None) -> int:
+    """Forget flat IDs (and dedup keys) first seen more than max_age_seconds ago;
+    returns how many flat IDs.
+
+    An evicted flat counts as new again when it is listed once more.
+    """
+    global _seen_flat_ids, _cache_modified, _cache_write_counter
+    cutoff = (now if now is not None else time.time()) - max_age_seconds
+    expired = [flat_id for flat_id, seen in _seen_flat_ids.items() if seen < cutoff]
+    for flat_id in expired:
+        del _seen_flat_ids[flat_id]
+    for key, sources in list(_sent_dedup_keys.items()):
+        for source in [source for source, sent in sources.items() if sent < cutoff]:
+            del sources[source]
+            _cache_modified = True
+        if not sources:
+            del _sent_dedup_keys[key]
+    if expired:
+        _cache_modified = True
+        _cache_write_counter += len(expired)
+    return len(expired)
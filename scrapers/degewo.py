@@ -1,76 +1,130 @@
 """Degewo scraper."""
 
+import asyncio
 import logging
 from typing import List, Optional
+from urllib.parse import urljoin
 
-import aiohttp
 from bs4 import BeautifulSoup
 
 from .base import (
     BaseScraper,
     FlatDetails,
     HighTrafficError,
+    ParsingError,
     StandardFields,
     WebsiteUnavailableError,
-    check_wbs_required,
+    compile_selectors,
+    detect_wbs,
+    select_first,
 )
+from .session import get_session
 
 logger = logging.getLogger(__name__)
 
+# Candidate selectors per field, tried in order until one matches
+TITLE_SELECTORS = compile_selectors(["h2.article__title", ".article__title", "h2", "h3"])
+LINK_SELECTORS = compile_selectors(
+    ["h2.article__title a[href]", "a.article__link[href]", "a[href]"]
+)
+PRICE_SELECTORS = compile_selectors(
+    [
+        "div.article__price-tag span.price",
+        ".article__price-tag .price",
+        "span.price",
+    ]
+)
+NEXT_PAGE_SELECTORS = compile_selectors(
+    [
+        "a[rel='next'][href]",
+        ".pagination__item--next a[href]",
+        ".pagination a.next[href]",
+        "a.pager__next[href]",
+    ]
+)
+# Pause between result pages to stay polite
+PAGE_DELAY = 1.0
+
 
 class DegewoScraper(BaseScraper):
-    async def fetch_flats(self) -> List[FlatDetails]:
+    async def fetch_flats(self, url: Optional[str] = None) -> List[FlatDetails]:
         logger.info("Fetching flats from Degewo...")
         try:
-            async with aiohttp.ClientSession() as session:
-                success, html = await self._make_request(session)
+            session = await get_session()
+            flats = []
+            page_url = url or self.url
+            for page in range(1, self.max_pages + 1):
+                if page > 1:
+                    await asyncio.sleep(PAGE_DELAY)
+                # Backoff is checked before every page request
+                try:
+                    success, html = await self._make_request(session, url=page_url)
+                except (WebsiteUnavailableError, HighTrafficError) as e:
+                    if page == 1:
+                        raise
+                    # Keep the flats of the pages fetched so far
+                    logger.warning(f"Stopping Degewo pagination at page {page}: {e}")
+                    break
                 if not success:
-                    return []
-
-                soup = BeautifulSoup(html, "html.parser")
-                flats = []
-
-                # Check for high traffic message
-                if (
-                    soup.find("div", class_="error-message")
-                    and "high traffic" in soup.text.lower()
-                ):
-                    raise HighTrafficError("Website experiencing high traffic")
-
-                # Find all flat elements
-                flat_elements = soup.find_all(
-                    "article",
-                    class_="article-list__item article-list__item--immosearch",
-                )
-                logger.info(f"Found {len(flat_elements)} flat elements in HTML")
-
-                for flat in flat_elements:
-                    flat_details = self._extract_flat_details(flat)
-                    if flat_details:
-                        flats.append(flat_details)
-
-                # Filter out duplicates within this fetch
-                flats = self._filter_duplicates(flats)
-                logger.debug(f"Flat IDs found: {[flat.id for flat in flats]}")
-                return flats
-        except (WebsiteUnavailableError, HighTrafficError) as e:
+                    break
+
+                page_flats, page_url = self._parse_page(html, page_url)
+                known_ids = {flat.id for flat in flats}
+                new_flats = [flat for flat in page_flats if flat.id not in known_ids]
+                logger.info(f"Degewo page {page}: {len(new_flats)} new flats")
+                flats.extend(new_flats)
+                if not new_flats or not page_url:
+                    break
+
+            # Filter out duplicates within this fetch
+            flats = self._filter_duplicates(flats)
+            logger.debug(f"Flat IDs found: {[flat.id for flat in flats]}")
+            return flats
+        except (WebsiteUnavailableError, HighTrafficError, ParsingError) as e:
             logger.error(f"Error fetching flats from Degewo: {e}")
             raise
         except Exception as e:
             logger.error(f"Unexpected error fetching flats from Degewo: {e}")
             return []
 
+    def _parse_page(self, html: str, page_url: str):
+        """Parse one result page. Returns its flats and the next page URL, if any."""
+        soup = BeautifulSoup(html, "html.parser")
+
+        # Check for high traffic or maintenance messages
+        self.check_high_traffic(soup)
+
+        # Find all flat elements
+        flat_elements = soup.find_all(
+            "article",
+            class_="article-list__item article-list__item--immosearch",
+        )
+        logger.info(f"Found {len(flat_elements)} flat elements in HTML")
+
+        flats = []
+        for flat in flat_elements:
+            flat_details = self._extract_flat_details(flat)
+            if flat_details:
+                flats.append(flat_details)
+        self._check_parse_failures(len(flat_elements), len(flats))
+
+        next_link = select_first(soup, NEXT_PAGE_SELECTORS)
+        next_url = urljoin(page_url, next_link["href"]) if next_link else None
+        if next_url == page_url:
+            next_url = None
+        return flats, next_url
+
     def _extract_flat_details(self, flat_element) -> Optional[FlatDetails]:
         try:
             # Extract the unique ID from the article's ID attribute
             flat_id = flat_element.get("id", "").replace("immobilie-list-item-", "")
 
             # Extract the title of the flat
-            title_element = flat_element.find("h2", class_="article__title")
+            title_element = select_first(flat_element, TITLE_SELECTORS)
             title_text = title_element.text.strip() if title_element else "No title"
 
             # Extract the link to the flat's details
-            link_element = flat_element.find("a", href=True)
+            link_element = select_first(flat_element, LINK_SELECTORS)
             link = link_element["href"] if link_element else None
             if link and not link.startswith("http"):
                 link = f"https://www.degewo.de{link}"
@@ -104,15 +158,12 @@ class DegewoScraper(BaseScraper):
                     ).text.strip()
 
             # Extract price
-            price_element = flat_element.find("div", class_="article__price-tag")
+            price_element = select_first(flat_element, PRICE_SELECTORS)
             if price_element:
-                price_text = price_element.find("span", class_="price")
-                if price_text:
-                    details[StandardFields.RENT_WARM] = price_text.text.strip()
+                details[StandardFields.RENT_WARM] = price_element.text.strip()
 
             # Determine if WBS is required - check title and all details
-            wbs_sources = [title_text] + [str(v) for v in details.values() if v]
-            wbs_required = any(check_wbs_required(source) for source in wbs_sources)
+            wbs_required = detect_wbs(title_text, details)
 
             # Return the flat details
             return FlatDetails(
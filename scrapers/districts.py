@@ -0,0 +1,86 @@
+"""Berlin district (Bezirk) and locality (Ortsteil) detection."""
+
+import re
+from typing import Dict, List, Optional, Set
+
+# Berlin Bezirke and their Ortsteile
+BERLIN_DISTRICTS: Dict[str, List[str]] = {
+    "Mitte": ["Mitte", "Moabit", "Hansaviertel", "Tiergarten", "Wedding", "Gesundbrunnen"],
+    "Friedrichshain-Kreuzberg": ["Friedrichshain", "Kreuzberg"],
+    "Pankow": [
+        "Prenzlauer Berg", "Weißensee", "Blankenburg", "Heinersdorf", "Karow",
+        "Stadtrandsiedlung Malchow", "Pankow", "Blankenfelde", "Buch",
+        "Französisch Buchholz", "Niederschönhausen", "Rosenthal", "Wilhelmsruh",
+    ],
+    "Charlottenburg-Wilmersdorf": [
+        "Charlottenburg", "Wilmersdorf", "Schmargendorf", "Grunewald", "Westend",
+        "Charlottenburg-Nord", "Halensee",
+    ],
+    "Spandau": [
+        "Spandau", "Haselhorst", "Siemensstadt", "Staaken", "Gatow", "Kladow",
+        "Hakenfelde", "Falkenhagener Feld", "Wilhelmstadt",
+    ],
+    "Steglitz-Zehlendorf": [
+        "Steglitz", "Lichterfelde", "Lankwitz", "Zehlendorf", "Dahlem",
+        "Nikolassee", "Wannsee",
+    ],
+    "Tempelhof-Schöneberg": [
+        "Schöneberg", "Friedenau", "Tempelhof", "Mariendorf", "Marienfelde", "Lichtenrade",
+    ],
+    "Neukölln": ["Neukölln", "Britz", "Buckow", "Rudow", "Gropiusstadt"],
+    "Treptow-Köpenick": [
+        "Alt-Treptow", "Treptow", "Plänterwald", "Baumschulenweg", "Johannisthal",
+        "Niederschöneweide", "Altglienicke", "Adlershof", "Bohnsdorf",
+        "Oberschöneweide", "Köpenick", "Friedrichshagen", "Rahnsdorf", "Grünau",
+        "Müggelheim", "Schmöckwitz",
+    ],
+    "Marzahn-Hellersdorf": ["Marzahn", "Biesdorf", "Kaulsdorf", "Mahlsdorf", "Hellersdorf"],
+    "Lichtenberg": [
+        "Friedrichsfelde", "Karlshorst", "Lichtenberg", "Falkenberg", "Malchow",
+        "Wartenberg", "Neu-Hohenschönhausen", "Alt-Hohenschönhausen",
+        "Hohenschönhausen", "Fennpfuhl", "Rummelsburg",
+    ],
+    "Reinickendorf": [
+        "Reinickendorf", "Tegel", "Konradshöhe", "Heiligensee", "Frohnau",
+        "Hermsdorf", "Waidmannslust", "Lübars", "Wittenau", "Märkisches Viertel",
+        "Borsigwalde",
+    ],
+}
+
+# Every known name mapped to its Bezirk, longest names first so that
+# "Charlottenburg-Nord" wins over "Charlottenburg"
+_NAME_TO_DISTRICT: Dict[str, str] = {}
+for _district, _localities in BERLIN_DISTRICTS.items():
+    _NAME_TO_DISTRICT[_district] = _district
+    for _locality in _localities:
+        _NAME_TO_DISTRICT.setdefault(_locality, _district)
+_NAME_PATTERN = re.compile(
+    r"(?<![\w-])("
+    + "|".join(re.escape(name) for name in sorted(_NAME_TO_DISTRICT, key=len, reverse=True))
+    + r")(?![\w-])",
+    re.IGNORECASE,
+)
+_CANONICAL_NAMES = {name.lower(): name for name in _NAME_TO_DISTRICT}
+
+
+def find_district_names(text: Optional[str]) -> Set[str]:
+    """
+    Find known Berlin Bezirk/Ortsteil names in a text.
+    Returns the matched names together with the Bezirk they belong to.
+    Street names like "Köpenicker Straße" don't match since whole words are required.
+    """
+    if not text:
+        return set()
+
+    names = set()
+    for match in _NAME_PATTERN.finditer(text):
+        name = _CANONICAL_NAMES[match.group(1).lower()]
+        names.add(name)
+        names.add(_NAME_TO_DISTRICT[name])
+    return names
+
+
+def district_for_name(name: str) -> Optional[str]:
+    """Return the Bezirk for a known Bezirk or Ortsteil name."""
+    canonical = _CANONICAL_NAMES.get(name.strip().lower())
+    return _NAME_TO_DISTRICT[canonical] if canonical else None
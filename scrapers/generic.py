@@ -0,0 +1,158 @@
+"""Scraper for simple listing pages, configured with CSS selectors in config.json."""
+
+import hashlib
+import logging
+from typing import Dict, List, Optional, Union
+from urllib.parse import urljoin
+
+import soupsieve
+
+from .base import (
+    BaseScraper,
+    FlatDetails,
+    HighTrafficError,
+    ParsingError,
+    WebsiteUnavailableError,
+    detect_wbs,
+    extract_rent_details,
+    parse_listing_date,
+)
+from .session import get_session
+
+logger = logging.getLogger(__name__)
+
+
+class GenericScraper(BaseScraper):
+    """
+    Scrapes any page listing one flat per element matched by item_selector.
+    Details are read with a map of StandardFields name -> CSS selector, the
+    title and link fall back to the first heading and link of the item.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        url: Union[str, List[str]],
+        item_selector: str,
+        details: Optional[Dict[str, str]] = None,
+        title_selector: Optional[str] = None,
+        link_selector: Optional[str] = None,
+        listed_at_selector: Optional[str] = None,
+    ):
+        super().__init__(url)
+        self._name = name
+        # Selectors are compiled once here; an invalid one fails at startup
+        self.item_selector = soupsieve.compile(item_selector)
+        self.detail_selectors = {
+            field_name: soupsieve.compile(selector)
+            for field_name, selector in (details or {}).items()
+        }
+        self.title_selector = soupsieve.compile(title_selector or "h1, h2, h3, h4")
+        self.link_selector = soupsieve.compile(link_selector or "a[href]")
+        # Element with the date the flat was posted, e.g. "01.03.2025"
+        self.listed_at_selector = (
+            soupsieve.compile(listed_at_selector) if listed_at_selector else None
+        )
+
+    @classmethod
+    def from_config(cls, config: Dict) -> "GenericScraper":
+        """Create a scraper from a GENERIC_SCRAPERS entry. Raises KeyError if incomplete."""
+        return cls(
+            name=config["name"],
+            url=config["url"],
+            item_selector=config["item_selector"],
+            details=config.get("details"),
+            title_selector=config.get("title_selector"),
+            link_selector=config.get("link_selector"),
+            listed_at_selector=config.get("listed_at_selector"),
+        )
+
+    @property
+    def name(self) -> str:
+        return self._name
+
+    async def fetch_flats(self, url: Optional[str] = None) -> List[FlatDetails]:
+        logger.info(f"Fetching flats from {self.name}...")
+        try:
+            session = await get_session()
+            success, html = await self._make_request(session, url=url)
+            if not success:
+                return []
+
+            flats = self.parse_flats(html, url or self.url)
+            return self._filter_duplicates(flats)
+        except (WebsiteUnavailableError, HighTrafficError, ParsingError) as e:
+            logger.error(f"Error fetching flats from {self.name}: {e}")
+            raise
+        except Exception as e:
+            logger.error(f"Unexpected error fetching flats from {self.name}: {e}")
+            return []
+        finally:
+            self._cleanup()
+
+    def parse_flats(self, html: str, page_url: Optional[str] = None) -> List[FlatDetails]:
+        """Parse all listings of a page; relative links are resolved against page_url."""
+        soup = self._parse_html(html)
+        self.check_high_traffic(soup)
+        flat_elements = self.item_selector.select(soup)
+        logger.info(f"Found {len(flat_elements)} flat elements in HTML")
+
+        flats = []
+        for flat in flat_elements:
+            flat_details = self._extract_flat_details(flat, page_url or self.url)
+            if flat_details:
+                flats.append(flat_details)
+        self._check_parse_failures(len(flat_elements), len(flats))
+        return flats
+
+    def _extract_flat_details(self, flat_element, page_url: str) -> Optional[FlatDetails]:
+        try:
+            title_element = self.title_selector.select_one(flat_element)
+            if not title_element:
+                return None
+            title_text = title_element.get_text(" ", strip=True)
+
+            link = None
+            link_element = self.link_selector.select_one(flat_element)
+            if link_element and link_element.get("href"):
+                link = urljoin(page_url, link_element["href"])
+
+            details = {}
+            for field_name, selector in self.detail_selectors.items():
+                element = selector.select_one(flat_element)
+                if element:
+                    value = element.get_text(" ", strip=True)
+                    if value:
+                        details[field_name] = value
+            # Rent components printed as text, e.g. "Kaltmiete: 650 €", unless mapped
+            for field_name, value in extract_rent_details(
+                flat_element.get_text(" ", strip=True)
+            ).items():
+                details.setdefault(field_name, value)
+
+            # Check for WBS requirement - check title and all details
+            wbs_required = detect_wbs(title_text, details)
+
+            listed_at = None
+            if self.listed_at_selector:
+                element = self.listed_at_selector.select_one(flat_element)
+                if element:
+                    # <time datetime="..."> carries the machine-readable date
+                    listed_at = parse_listing_date(
+                        element.get("datetime") or element.get_text(" ", strip=True)
+                    )
+
+            return FlatDetails(
+                # Without a link, hash the text so the ID is stable across restarts
+                id=link
+                or hashlib.sha1(flat_element.get_text().encode("utf-8")).hexdigest()[:16],
+                title=title_text,
+                link=link,
+                details=details,
+                wbs_required=wbs_required,
+                source=self.name,
+                listed_at=listed_at,
+            )
+        except Exception as e:
+            logger.error(f"Error extracting flat details from {self.name}: {e}")
+            return None
@@ -3,53 +3,51 @@
 import logging
 from typing import List, Optional
 
-import aiohttp
 from bs4 import BeautifulSoup
 
 from .base import (
     BaseScraper,
     FlatDetails,
     HighTrafficError,
+    ParsingError,
     StandardFields,
     WebsiteUnavailableError,
-    check_wbs_required,
+    detect_wbs,
 )
+from .session import get_session
 
 logger = logging.getLogger(__name__)
 
 
 class GesobauScraper(BaseScraper):
-    async def fetch_flats(self) -> List[FlatDetails]:
+    async def fetch_flats(self, url: Optional[str] = None) -> List[FlatDetails]:
         logger.info("Fetching flats from Gesobau...")
         try:
-            async with aiohttp.ClientSession() as session:
-                success, html = await self._make_request(session)
-                if not success:
-                    return []
-
-                soup = BeautifulSoup(html, "html.parser")
-                flats = []
-
-                # Check for high traffic message
-                if (
-                    soup.find("div", class_="error-message")
-                    and "high traffic" in soup.text.lower()
-                ):
-                    raise HighTrafficError("Website experiencing high traffic")
-
-                # Find all flat elements
-                flat_elements = soup.find_all("div", class_="teaserList__item")
-                logger.info(f"Found {len(flat_elements)} flat elements in HTML")
-
-                for flat in flat_elements:
-                    flat_details = self._extract_flat_details(flat)
-                    if flat_details:
-                        flats.append(flat_details)
-
-                # Filter out duplicates within this fetch
-                flats = self._filter_duplicates(flats)
-                return flats
-        except (WebsiteUnavailableError, HighTrafficError) as e:
+            session = await get_session()
+            success, html = await self._make_request(session, url=url)
+            if not success:
+                return []
+
+            soup = BeautifulSoup(html, "html.parser")
+            flats = []
+
+            # Check for high traffic or maintenance messages
+            self.check_high_traffic(soup)
+
+            # Find all flat elements
+            flat_elements = soup.find_all("div", class_="teaserList__item")
+            logger.info(f"Found {len(flat_elements)} flat elements in HTML")
+
+            for flat in flat_elements:
+                flat_details = self._extract_flat_details(flat)
+                if flat_details:
+                    flats.append(flat_details)
+            self._check_parse_failures(len(flat_elements), len(flats))
+
+            # Filter out duplicates within this fetch
+            flats = self._filter_duplicates(flats)
+            return flats
+        except (WebsiteUnavailableError, HighTrafficError, ParsingError) as e:
             logger.error(f"Error fetching flats from Gesobau: {e}")
             raise
         except Exception as e:
@@ -98,8 +96,7 @@ class GesobauScraper(BaseScraper):
                     details[StandardFields.RENT_WARM] = info_spans[2].text.strip()
 
             # Check for WBS requirement - check title and all details
-            wbs_sources = [title_text] + [str(v) for v in details.values() if v]
-            wbs_required = any(check_wbs_required(source) for source in wbs_sources)
+            wbs_required = detect_wbs(title_text, details)
 
             return FlatDetails(
                 id=flat_id,
@@ -1,55 +1,54 @@
 """Gewobag scraper."""
 
 import logging
+import re
 from typing import List, Optional
 
-import aiohttp
 from bs4 import BeautifulSoup
 
 from .base import (
     BaseScraper,
     FlatDetails,
     HighTrafficError,
+    ParsingError,
     StandardFields,
     WebsiteUnavailableError,
-    check_wbs_required,
+    detect_wbs,
 )
+from .session import get_session
 
 logger = logging.getLogger(__name__)
 
 
 class GewobagScraper(BaseScraper):
-    async def fetch_flats(self) -> List[FlatDetails]:
+    async def fetch_flats(self, url: Optional[str] = None) -> List[FlatDetails]:
         logger.info("Fetching flats from Gewobag...")
         try:
-            async with aiohttp.ClientSession() as session:
-                success, html = await self._make_request(session)
-                if not success:
-                    return []
-
-                soup = BeautifulSoup(html, "html.parser")
-                flats = []
-
-                # Check for high traffic message
-                if (
-                    soup.find("div", class_="error-message")
-                    and "high traffic" in soup.text.lower()
-                ):
-                    raise HighTrafficError("Website experiencing high traffic")
-
-                # Find all flat elements
-                flat_elements = soup.find_all("article", class_="angebot-big-box")
-                logger.info(f"Found {len(flat_elements)} flat elements in HTML")
-
-                for flat in flat_elements:
-                    flat_details = self._extract_flat_details(flat)
-                    if flat_details:
-                        flats.append(flat_details)
-
-                # Filter out duplicates within this fetch
-                flats = self._filter_duplicates(flats)
-                return flats
-        except (WebsiteUnavailableError, HighTrafficError) as e:
+            session = await get_session()
+            success, html = await self._make_request(session, url=url)
+            if not success:
+                return []
+
+            soup = BeautifulSoup(html, "html.parser")
+            flats = []
+
+            # Check for high traffic or maintenance messages
+            self.check_high_traffic(soup)
+
+            # Find all flat elements
+            flat_elements = soup.find_all("article", class_="angebot-big-box")
+            logger.info(f"Found {len(flat_elements)} flat elements in HTML")
+
+            for flat in flat_elements:
+                flat_details = self._extract_flat_details(flat)
+                if flat_details:
+                    flats.append(flat_details)
+            self._check_parse_failures(len(flat_elements), len(flats))
+
+            # Filter out duplicates within this fetch
+            flats = self._filter_duplicates(flats)
+            return flats
+        except (WebsiteUnavailableError, HighTrafficError, ParsingError) as e:
             logger.error(f"Error fetching flats from Gewobag: {e}")
             raise
         except Exception as e:
@@ -88,10 +87,18 @@ class GewobagScraper(BaseScraper):
                     if address_element:
                         details[StandardFields.ADDRESS] = address_element.text.strip()
 
-                # Extract area info
+                # Extract area info - usually "2 Zimmer | 60,5 m²"
                 area_row = info_table.find("tr", class_="angebot-area")
                 if area_row:
-                    details[StandardFields.AREA] = area_row.find("td").text.strip()
+                    area_text = area_row.find("td").text.strip()
+                    room_match = re.search(r"(\d+(?:[.,]\d+)?)\s*Zimmer", area_text, re.IGNORECASE)
+                    area_match = re.search(r"(\d+(?:[.,]\d+)?)\s*m", area_text.split("|")[-1])
+                    if room_match:
+                        details[StandardFields.ROOMS] = room_match.group(1)
+                    if room_match and area_match:
+                        details[StandardFields.AREA] = f"{area_match.group(1)} m²"
+                    else:
+                        details[StandardFields.AREA] = area_text
 
                 # Extract availability
                 availability_row = info_table.find("tr", class_="availability")
@@ -114,9 +121,13 @@ class GewobagScraper(BaseScraper):
                     if characteristics:
                         details[StandardFields.FEATURES] = ", ".join(characteristics)
 
-            # Check for WBS requirement - check title and all details
-            wbs_sources = [title_text] + [str(v) for v in details.values() if v]
-            wbs_required = any(check_wbs_required(source) for source in wbs_sources)
+            # Check for WBS requirement - check title, listing badges and all details
+            badges = flat_element.find_all(
+                class_=lambda x: x and any(k in x for k in ("badge", "label", "angebot-tag"))
+            )
+            wbs_required = detect_wbs(
+                title_text, details, [badge.text.strip() for badge in badges]
+            )
 
             return FlatDetails(
                 id=flat_id,
@@ -10,14 +10,33 @@ from .base import (
     BaseScraper,
     FlatDetails,
     HighTrafficError,
+    ParsingError,
     StandardFields,
     WebsiteUnavailableError,
-    check_wbs_required,
+    compile_selectors,
+    decode_html,
+    detect_wbs,
+    select_first,
 )
-from .session import get_session
+from .session import get_session, wait_for_request_slot
 
 logger = logging.getLogger(__name__)
 
+# Candidate selectors per field for the HTML fallback, tried in order until one matches
+TITLE_SELECTORS = compile_selectors(
+    ["h2", "h3", "h4", ".title", ".headline", ".apartment-title"]
+)
+LINK_SELECTORS = compile_selectors(
+    [
+        "a.org-but[href]",
+        "a.btn[href]",
+        "a.button[href]",
+        "a.link[href]",
+        "a[href]",
+    ]
+)
+PRICE_SELECTORS = compile_selectors([".price", ".rent", ".miete", "[class*='price']"])
+
 
 class InBerlinWohnenScraper(BaseScraper):
     def __init__(self, url: str):
@@ -38,31 +57,32 @@ class InBerlinWohnenScraper(BaseScraper):
             "Cache-Control": "max-age=0",
         }
 
-    async def fetch_flats(self) -> List[FlatDetails]:
+    async def fetch_flats(self, url: Optional[str] = None) -> List[FlatDetails]:
         logger.info("Fetching flats from InBerlinWohnen...")
         try:
             session = await get_session()
 
             # Get the main page to establish session and get apartment data
+            await wait_for_request_slot()
             async with session.get(
-                self.url, headers=self.custom_headers, timeout=30
+                url or self.url,
+                headers=self._request_headers(self.custom_headers),
+                timeout=30,
             ) as response:
+                self.last_status = response.status
                 if response.status != 200:
                     raise WebsiteUnavailableError(
                         f"Website unavailable. Status: {response.status}"
                     )
 
-                html = await response.text()
+                html = decode_html(await response.read(), response.charset)
+                self._save_snapshot(html)
 
             soup = BeautifulSoup(html, "html.parser")
             flats = []
 
-            # Check for high traffic message
-            if (
-                soup.find("div", class_="error-message")
-                and "high traffic" in soup.text.lower()
-            ):
-                raise HighTrafficError("Website experiencing high traffic")
+            # Check for high traffic or maintenance messages
+            self.check_high_traffic(soup)
 
             # Look for Livewire component data embedded in the HTML
             # The data is in wire:snapshot attribute or script tags
@@ -92,6 +112,7 @@ class InBerlinWohnenScraper(BaseScraper):
                     flat_details = self._parse_livewire_apartment(apt_data)
                     if flat_details:
                         flats.append(flat_details)
+                self._check_parse_failures(len(apartment_data), len(flats))
 
             # If no Livewire data found, fall back to traditional scraping
             if not flats:
@@ -112,7 +133,7 @@ class InBerlinWohnenScraper(BaseScraper):
             )
             return flats
 
-        except (WebsiteUnavailableError, HighTrafficError) as e:
+        except (WebsiteUnavailableError, HighTrafficError, ParsingError) as e:
             logger.error(f"Error fetching flats from InBerlinWohnen: {e}")
             raise
         except Exception as e:
@@ -331,16 +352,8 @@ class InBerlinWohnenScraper(BaseScraper):
             if object_id:
                 details[StandardFields.OBJECT_ID] = object_id
 
-            # Check for WBS requirement
-            wbs_sources = [title]
-            if "wbs" in apartment_data:
-                wbs_sources.append(str(apartment_data["wbs"]))
-            # Also check all detail values
-            wbs_sources.extend(str(v) for v in details.values() if v)
-
-            wbs_required = any(
-                check_wbs_required(source) for source in wbs_sources if source
-            )
+            # Check for WBS requirement, including the raw "wbs" field
+            wbs_required = detect_wbs(title, details, [apartment_data.get("wbs")])
 
             return FlatDetails(
                 id=flat_id,
@@ -368,51 +381,19 @@ class InBerlinWohnenScraper(BaseScraper):
                     flat_id = str(hash(flat_element.get_text()[:100]))
 
             # Try multiple selectors for title
-            title = None
-            title_selectors = [
-                "h2",
-                "h3",
-                "h4",
-                ".title",
-                ".headline",
-                ".apartment-title",
-            ]
-            for selector in title_selectors:
-                title = flat_element.find(
-                    selector.replace(".", ""),
-                    class_=selector.replace(".", "") if "." in selector else None,
-                )
-                if not title and "." in selector:
-                    title = flat_element.find(class_=selector.replace(".", ""))
-                if title:
-                    break
-
+            title = select_first(flat_element, TITLE_SELECTORS)
             title_text = title.text.strip() if title else "No title"
 
             # Try multiple selectors for links
             link = None
-            link_selectors = [
-                ("a", "org-but"),
-                ("a", "btn"),
-                ("a", "button"),
-                ("a", "link"),
-                ("a", None),  # Any link
-            ]
-
-            for tag, class_name in link_selectors:
-                if class_name:
-                    link_element = flat_element.find(tag, class_=class_name)
-                else:
-                    link_element = flat_element.find(tag, href=True)
-
-                if link_element and link_element.get("href"):
-                    link = link_element["href"]
-                    if not link.startswith("http"):
-                        if link.startswith("/"):
-                            link = f"https://www.inberlinwohnen.de{link}"
-                        else:
-                            link = f"https://www.inberlinwohnen.de/{link}"
-                    break
+            link_element = select_first(flat_element, LINK_SELECTORS)
+            if link_element:
+                link = link_element["href"]
+                if not link.startswith("http"):
+                    if link.startswith("/"):
+                        link = f"https://www.inberlinwohnen.de{link}"
+                    else:
+                        link = f"https://www.inberlinwohnen.de/{link}"
 
             details = {}
 
@@ -510,14 +491,13 @@ class InBerlinWohnenScraper(BaseScraper):
                 if price_match:
                     details[StandardFields.RENT_WARM] = f"{price_match.group(1)} €"
 
-            # Check for WBS requirement - check title, details, and full element text
-            wbs_sources = [title_text, flat_element.get_text()]
-            # Also check all detail values
-            wbs_sources.extend(str(v) for v in details.values() if v)
+            # Prefer a dedicated price element over the text match
+            price_element = select_first(flat_element, PRICE_SELECTORS)
+            if price_element and price_element.text.strip():
+                details[StandardFields.RENT_WARM] = price_element.text.strip()
 
-            wbs_required = any(
-                check_wbs_required(source) for source in wbs_sources if source
-            )
+            # Check for WBS requirement - check title, details, and full element text
+            wbs_required = detect_wbs(title_text, details, [flat_element.get_text()])
 
             # Only return valid flats
             if flat_id and title_text and title_text != "No title":
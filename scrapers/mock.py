@@ -0,0 +1,47 @@
+"""Scraper returning programmable flats, for exercising FlatMonitor without websites."""
+
+import logging
+from typing import List, Optional
+
+from .base import BackoffActiveError, BaseScraper, FlatDetails
+
+logger = logging.getLogger(__name__)
+
+
+class MockScraper(BaseScraper):
+    """
+    Returns the flats in self.flats on every fetch, without any request.
+    Change self.flats between cycles to simulate new or removed listings,
+    fail_next() to simulate an outage; a failure starts the backoff like a
+    real HTTP error does.
+    """
+
+    def __init__(self, name: str = "MockScraper", flats: Optional[List[FlatDetails]] = None):
+        super().__init__("mock://flats")
+        self._name = name
+        self.flats: List[FlatDetails] = list(flats or [])
+        self.fetch_count = 0
+        self._next_error: Optional[Exception] = None
+
+    @property
+    def name(self) -> str:
+        return self._name
+
+    def fail_next(self, error: Exception):
+        """Raise error from the next fetch."""
+        self._next_error = error
+
+    async def fetch_flats(self, url: Optional[str] = None) -> List[FlatDetails]:
+        if self._check_backoff():
+            raise BackoffActiveError(
+                f"Website is in backoff period. Retry in {self.backoff_time} seconds."
+            )
+        self.fetch_count += 1
+        if self._next_error is not None:
+            error, self._next_error = self._next_error, None
+            self._update_backoff()
+            raise error
+        self.last_status = 200
+        self._reset_backoff()
+        logger.info(f"{self.name}: returning {len(self.flats)} mock flats")
+        return self._filter_duplicates(list(self.flats))
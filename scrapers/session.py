@@ -1,12 +1,107 @@
 """Global session management for HTTP requests."""
 
+import asyncio
+import random
 import ssl
+import time
+from typing import Dict, List, Optional
 
 import aiohttp
 import certifi
 
 # Global session for connection pooling
 _global_session = None
+# Default Accept-Language sent with every request (German content keeps parsing stable)
+_accept_language = "de-DE,de;q=0.9"
+DEFAULT_USER_AGENT = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.4 Safari/605.1.15"
+# User agents to rotate through, one picked at random per request (empty = session default)
+_user_agents: List[str] = []
+# Proxy all scraper requests go through (http(s):// or socks5://)
+_proxy_url: Optional[str] = None
+
+
+class RateLimiter:
+    """Spaces requests evenly so at most max_per_minute start in any minute."""
+
+    def __init__(self, max_per_minute: int):
+        self.interval = 60.0 / max_per_minute
+        self._next_slot = 0.0
+        self._lock = asyncio.Lock()
+
+    async def acquire(self):
+        """Wait until the next request may be sent."""
+        async with self._lock:
+            now = time.monotonic()
+            delay = self._next_slot - now
+            self._next_slot = max(now, self._next_slot) + self.interval
+        if delay > 0:
+            await asyncio.sleep(delay)
+
+
+# Shared by all scrapers (None = unlimited)
+_rate_limiter: Optional[RateLimiter] = None
+
+
+def configure_session(
+    accept_language: str = None,
+    user_agents: List[str] = None,
+    proxy_url: str = None,
+    max_requests_per_minute: int = None,
+):
+    """Configure default request settings. Call before the first request is made."""
+    global _accept_language, _user_agents, _proxy_url, _rate_limiter
+    if accept_language:
+        _accept_language = accept_language
+    if user_agents:
+        _user_agents = list(user_agents)
+    if proxy_url:
+        _proxy_url = proxy_url
+    if max_requests_per_minute:
+        _rate_limiter = RateLimiter(max_requests_per_minute)
+
+
+async def wait_for_request_slot():
+    """Wait for the shared rate limiter, if one is configured. Call before every request."""
+    if _rate_limiter is not None:
+        await _rate_limiter.acquire()
+
+
+def _create_connector(ssl_context: ssl.SSLContext) -> aiohttp.BaseConnector:
+    connector_options = dict(
+        ssl=ssl_context,
+        limit=5,  # Limit concurrent connections
+        ttl_dns_cache=300,  # Cache DNS results for 5 minutes
+        use_dns_cache=True,
+        force_close=False,  # Keep connections alive
+        enable_cleanup_closed=True,
+    )
+    if _proxy_url and _proxy_url.startswith("socks"):
+        # aiohttp only speaks HTTP proxies natively
+        from aiohttp_socks import ProxyConnector
+
+        return ProxyConnector.from_url(_proxy_url, **connector_options)
+    return aiohttp.TCPConnector(**connector_options)
+
+
+def request_headers(headers: Optional[Dict[str, str]] = None) -> Dict[str, str]:
+    """
+    Return headers for a single request, with a randomly chosen User-Agent
+    if a list was configured and the given headers don't set one. Without a
+    list, the given headers (or the session default) decide the User-Agent.
+    """
+    headers = dict(headers or {})
+    if _user_agents and "User-Agent" not in headers:
+        headers["User-Agent"] = random.choice(_user_agents)
+    return headers
+
+
+def session_headers() -> Dict[str, str]:
+    """Default headers of the shared session, sent with every scraper request."""
+    return {
+        "User-Agent": DEFAULT_USER_AGENT,
+        "Accept": "*/*",
+        "Accept-Language": _accept_language,
+    }
 
 
 async def get_session() -> aiohttp.ClientSession:
@@ -17,24 +112,15 @@ async def get_session() -> aiohttp.ClientSession:
         ssl_context = ssl.create_default_context(cafile=certifi.where())
 
         # Configure TCP connector with optimized settings
-        connector = aiohttp.TCPConnector(
-            ssl=ssl_context,
-            limit=5,  # Limit concurrent connections
-            ttl_dns_cache=300,  # Cache DNS results for 5 minutes
-            use_dns_cache=True,
-            force_close=False,  # Keep connections alive
-            enable_cleanup_closed=True,
-        )
+        connector = _create_connector(ssl_context)
 
         # Create session with optimized settings
         _global_session = aiohttp.ClientSession(
             connector=connector,
+            # HTTP proxies are set on the session, SOCKS proxies on the connector
+            proxy=_proxy_url if _proxy_url and _proxy_url.startswith("http") else None,
             timeout=aiohttp.ClientTimeout(total=30),
-            headers={
-                "User-Agent": "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.4 Safari/605.1.15",
-                "Accept": "*/*",
-                "Accept-Language": "en-GB,en;q=0.9",
-            },
+            headers=session_headers(),
         )
     return _global_session
 
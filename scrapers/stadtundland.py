@@ -1,5 +1,6 @@
 """Stadt und Land scraper."""
 
+import json
 import logging
 from typing import List, Optional
 from urllib.parse import quote
@@ -10,17 +11,24 @@ from .base import (
     BaseScraper,
     FlatDetails,
     HighTrafficError,
+    ParsingError,
     StandardFields,
     WebsiteUnavailableError,
-    check_wbs_required,
+    detect_wbs,
 )
 from .session import get_session
 
 logger = logging.getLogger(__name__)
 
+# Search API behind the listings on stadtundland.de/wohnungssuche
+API_URL = "https://d2396ha8oiavw0.cloudfront.net/sul-main/immoSearch"
+
 
 class StadtUndLandScraper(BaseScraper):
-    async def fetch_flats(self) -> List[FlatDetails]:
+    # Listings come from a fixed search API, not from the website URL
+    supports_urls = False
+
+    async def fetch_flats(self, url: Optional[str] = None) -> List[FlatDetails]:
         logger.info("Fetching flats from Stadt und Land...")
         try:
             session = await get_session()
@@ -36,47 +44,44 @@ class StadtUndLandScraper(BaseScraper):
                 "Sec-Fetch-Site": "cross-site",
             }
 
-            api_url = "https://d2396ha8oiavw0.cloudfront.net/sul-main/immoSearch"
-            payload = {"offset": 0, "cat": "wohnung"}
-
-            # Make the API request directly
-            async with session.post(
-                api_url, json=payload, headers=headers, timeout=30, allow_redirects=True
-            ) as response:
-                if response.status != 200:
-                    logger.error(f"API response status: {response.status}")
-                    logger.error(f"API response headers: {response.headers}")
-                    response_text = await response.text()
-                    logger.error(f"API response text: {response_text}")
-                    raise WebsiteUnavailableError(
-                        f"API returned status code {response.status}"
-                    )
-
-                try:
-                    data = await response.json()
-                    flats_data = data.get("data", [])
-
-                    if not flats_data:
-                        logger.info("No flats found in Stadt und Land response")
-                        return []
-
-                    flats = []
-                    for flat_data in flats_data:
-                        flat_details = self._extract_flat_details(flat_data)
-                        if flat_details:
-                            flats.append(flat_details)
-
-                    # Filter out duplicates
-                    flats = self._filter_duplicates(flats)
-                    logger.info(f"Found {len(flats)} flats from Stadt und Land")
-                    self._cleanup()
-                    return flats
-
-                except Exception as e:
-                    logger.error(f"Failed to parse JSON response: {e}")
-                    raise WebsiteUnavailableError("Failed to parse API response")
-
-        except (WebsiteUnavailableError, HighTrafficError) as e:
+            success, response_text = await self._make_request(
+                session,
+                method="POST",
+                url=API_URL,
+                snapshot_extension="json",
+                json={"offset": 0, "cat": "wohnung"},
+                headers=headers,
+                timeout=30,
+                allow_redirects=True,
+            )
+            if not success:
+                return []
+
+            try:
+                data = json.loads(response_text)
+            except json.JSONDecodeError as e:
+                logger.error(f"Failed to parse JSON response: {e}")
+                raise WebsiteUnavailableError("Failed to parse API response")
+
+            flats_data = data.get("data", [])
+            if not flats_data:
+                logger.info("No flats found in Stadt und Land response")
+                return []
+
+            flats = []
+            for flat_data in flats_data:
+                flat_details = self._extract_flat_details(flat_data)
+                if flat_details:
+                    flats.append(flat_details)
+            self._check_parse_failures(len(flats_data), len(flats))
+
+            # Filter out duplicates
+            flats = self._filter_duplicates(flats)
+            logger.info(f"Found {len(flats)} flats from Stadt und Land")
+            self._cleanup()
+            return flats
+
+        except (WebsiteUnavailableError, HighTrafficError, ParsingError) as e:
             logger.error(f"Error fetching flats from Stadt und Land: {e}")
             raise
         except Exception as e:
@@ -134,8 +139,7 @@ class StadtUndLandScraper(BaseScraper):
             if special_features:
                 details[StandardFields.FEATURES] = ", ".join(special_features)
 
-            wbs_sources = [title] + [str(v) for v in details.values() if v]
-            wbs_required = any(check_wbs_required(source) for source in wbs_sources)
+            wbs_required = detect_wbs(title, details)
 
             return FlatDetails(
                 id=flat_id,
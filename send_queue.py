@@ -0,0 +1,78 @@
+"""Single queue for outgoing Telegram messages.
+
+Every notification, alert and summary goes through SendQueue, so bursts
+from several code paths are sent one at a time, at most one message per
+SEND_INTERVAL_MS. When Telegram still answers with RetryAfter, the whole
+queue pauses for the requested time instead of each sender retrying on its own.
+"""
+
+import asyncio
+import logging
+from datetime import timedelta
+from typing import Awaitable, Callable, Optional, Tuple
+
+from telegram.error import RetryAfter
+
+logger = logging.getLogger(__name__)
+
+
+def retry_after_seconds(error: RetryAfter) -> float:
+    """Seconds Telegram asks us to wait after flooding (retry_after may be a timedelta)."""
+    retry_after = error.retry_after
+    if isinstance(retry_after, timedelta):
+        return retry_after.total_seconds()
+    return float(retry_after)
+
+
+class SendQueue:
+    """Dispatches queued sends in order through one worker task."""
+
+    def __init__(self, send: Callable[..., Awaitable], interval_seconds: float = 1.0):
+        # Called with the keyword arguments given to send(), e.g. Bot.send_message
+        self._send = send
+        self.interval_seconds = interval_seconds
+        self._queue: "asyncio.Queue[Tuple[dict, asyncio.Future]]" = asyncio.Queue()
+        self._worker: Optional[asyncio.Task] = None
+
+    async def send(self, **kwargs):
+        """Queue a send and wait until it was dispatched; raises the send's error."""
+        if self._worker is None or self._worker.done():
+            self._worker = asyncio.create_task(self._run())
+        future = asyncio.get_running_loop().create_future()
+        await self._queue.put((kwargs, future))
+        return await future
+
+    async def _run(self):
+        while True:
+            kwargs, future = await self._queue.get()
+            try:
+                if not future.cancelled():
+                    result = await self._dispatch(kwargs)
+                    if not future.cancelled():
+                        future.set_result(result)
+            except Exception as e:
+                if not future.cancelled():
+                    future.set_exception(e)
+            finally:
+                self._queue.task_done()
+            await asyncio.sleep(self.interval_seconds)
+
+    async def _dispatch(self, kwargs: dict):
+        """Send once, waiting once for Telegram's retry_after when rate limited."""
+        try:
+            return await self._send(**kwargs)
+        except RetryAfter as e:
+            delay = retry_after_seconds(e)
+            logger.warning(f"Rate limited by Telegram, pausing sends for {delay:g}s")
+            await asyncio.sleep(delay)
+            return await self._send(**kwargs)
+
+    async def close(self):
+        """Stop the worker; messages still queued are dropped."""
+        if self._worker is not None:
+            self._worker.cancel()
+            try:
+                await self._worker
+            except asyncio.CancelledError:
+                pass
+            self._worker = None
@@ -0,0 +1,49 @@
+"""Persistent bot state, e.g. filters changed at runtime via Telegram commands."""
+
+import json
+import logging
+from pathlib import Path
+from typing import Any, Dict, Optional
+
+logger = logging.getLogger(__name__)
+
+
+class StateStore:
+    """Small JSON key/value store backed by STATE_FILE.
+
+    Without a path the state only lives in memory. Writes happen only when a
+    value changes (commands are rare), so this is safe for SD cards.
+    """
+
+    def __init__(self, path: Optional[str] = None):
+        self.path = Path(path) if path else None
+        self._data: Dict[str, Any] = {}
+        self.load()
+
+    def load(self):
+        if self.path is None or not self.path.exists():
+            return
+        try:
+            with open(self.path, "r") as f:
+                self._data = json.load(f)
+            logger.info(f"Loaded bot state from {self.path}")
+        except (json.JSONDecodeError, IOError) as e:
+            logger.error(f"Failed to load state file {self.path}: {e}")
+            self._data = {}
+
+    def save(self):
+        if self.path is None:
+            return
+        try:
+            with open(self.path, "w") as f:
+                json.dump(self._data, f, separators=(",", ":"))
+        except IOError as e:
+            logger.error(f"Failed to save state file {self.path}: {e}")
+
+    def get(self, key: str, default: Any = None) -> Any:
+        return self._data.get(key, default)
+
+    def set(self, key: str, value: Any):
+        """Store a value and persist the state file."""
+        self._data[key] = value
+        self.save()
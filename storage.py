@@ -0,0 +1,234 @@
+"""Persistence of seen flats, flat snapshots and notification history.
+
+JsonStore (the default) keeps the seen flat IDs in the RAM disk cache of
+scrapers.cache. SqliteStore is used when DATABASE_URL is set and also stores
+full flat snapshots and every notification with its timestamp.
+"""
+
+import json
+import logging
+import sqlite3
+from datetime import datetime, timedelta
+from typing import Dict, List, Optional, Set
+
+from scrapers import (
+    FlatDetails,
+    evict_seen_flats,
+    load_seen_flats,
+    mark_flats_as_seen,
+    reset_seen_flats,
+    save_seen_flats,
+)
+from scrapers.cache import is_flat_seen, sent_dedup_sources
+
+logger = logging.getLogger(__name__)
+
+
+class Store:
+    """Interface of the persistence backends."""
+
+    def load(self):
+        """Prepare the store at startup."""
+
+    def is_seen(self, flat_id: str) -> bool:
+        raise NotImplementedError
+
+    def mark_seen(self, flats: List[FlatDetails]):
+        """Remember these notified flats by ID and by dedup key."""
+        raise NotImplementedError
+
+    def sent_dedup_sources(self, keys: List[str]) -> Dict[str, Set[str]]:
+        """Sources of flats notified with these dedup keys, by key, to skip
+        a flat another website listed in an earlier check."""
+        raise NotImplementedError
+
+    def reset_seen(self):
+        raise NotImplementedError
+
+    def evict_seen(self, max_age: timedelta, now: Optional[datetime] = None) -> int:
+        """Forget flats first seen longer than max_age ago, so they can notify again.
+
+        Returns the number of forgotten flats.
+        """
+        raise NotImplementedError
+
+    def save_snapshots(self, flats: List[FlatDetails]):
+        """Store the latest version of each flat."""
+
+    def record_notifications(self, flats: List[FlatDetails], sent_at: Optional[datetime] = None):
+        """Remember that users were notified about these flats."""
+
+    def close(self):
+        """Flush pending writes on shutdown."""
+
+
+class JsonStore(Store):
+    """Seen flat IDs in the /dev/shm JSON cache. Snapshots and history are not kept."""
+
+    def load(self):
+        load_seen_flats()
+
+    def is_seen(self, flat_id: str) -> bool:
+        return is_flat_seen(flat_id)
+
+    def mark_seen(self, flats: List[FlatDetails]):
+        mark_flats_as_seen(flats)
+        # Only writes once enough flats were added, to spare the disk
+        save_seen_flats()
+
+    def sent_dedup_sources(self, keys: List[str]) -> Dict[str, Set[str]]:
+        return sent_dedup_sources(keys)
+
+    def reset_seen(self):
+        reset_seen_flats()
+
+    def evict_seen(self, max_age: timedelta, now: Optional[datetime] = None) -> int:
+        evicted = evict_seen_flats(
+            max_age.total_seconds(), now.timestamp() if now else None
+        )
+        save_seen_flats()
+        return evicted
+
+    def close(self):
+        save_seen_flats(force=True)
+
+
+# Schema migrations, applied in order; PRAGMA user_version holds the number applied
+MIGRATIONS = [
+    """
+    CREATE TABLE seen_flats (
+        id TEXT PRIMARY KEY,
+        first_seen TEXT NOT NULL
+    );
+    CREATE TABLE flat_snapshots (
+        id TEXT PRIMARY KEY,
+        source TEXT NOT NULL,
+        data TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE TABLE notifications (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        flat_id TEXT NOT NULL,
+        source TEXT NOT NULL,
+        sent_at TEXT NOT NULL
+    );
+    CREATE INDEX notifications_sent_at ON notifications (sent_at);
+    """,
+    """
+    CREATE TABLE sent_dedup_keys (
+        key TEXT NOT NULL,
+        source TEXT NOT NULL,
+        first_seen TEXT NOT NULL,
+        PRIMARY KEY (key, source)
+    );
+    """,
+]
+
+
+class SqliteStore(Store):
+    """SQLite database; every write is committed right away, so a crash loses nothing."""
+
+    def __init__(self, path: str):
+        self.path = path
+        self.connection: Optional[sqlite3.Connection] = None
+
+    def load(self):
+        self.connection = sqlite3.connect(self.path)
+        self.connection.execute("PRAGMA journal_mode=WAL")
+        self.migrate()
+        count = self.connection.execute("SELECT COUNT(*) FROM seen_flats").fetchone()[0]
+        logger.info(f"Opened database {self.path} with {count} seen flats")
+
+    def migrate(self):
+        version = self.connection.execute("PRAGMA user_version").fetchone()[0]
+        for number, script in enumerate(MIGRATIONS[version:], start=version + 1):
+            logger.info(f"Migrating database {self.path} to version {number}")
+            with self.connection:
+                self.connection.executescript(script)
+                self.connection.execute(f"PRAGMA user_version = {number}")
+
+    def is_seen(self, flat_id: str) -> bool:
+        row = self.connection.execute(
+            "SELECT 1 FROM seen_flats WHERE id = ?", (flat_id,)
+        ).fetchone()
+        return row is not None
+
+    def mark_seen(self, flats: List[FlatDetails]):
+        now = datetime.now().isoformat()
+        with self.connection:
+            self.connection.executemany(
+                "INSERT OR IGNORE INTO seen_flats (id, first_seen) VALUES (?, ?)",
+                [(flat.id, now) for flat in flats],
+            )
+            self.connection.executemany(
+                "INSERT OR IGNORE INTO sent_dedup_keys (key, source, first_seen) VALUES (?, ?, ?)",
+                [
+                    (flat.dedup_key(), flat.source, now)
+                    for flat in flats
+                    if flat.dedup_key() is not None
+                ],
+            )
+
+    def sent_dedup_sources(self, keys: List[str]) -> Dict[str, Set[str]]:
+        sources: Dict[str, Set[str]] = {}
+        for key in keys:
+            rows = self.connection.execute(
+                "SELECT source FROM sent_dedup_keys WHERE key = ?", (key,)
+            ).fetchall()
+            if rows:
+                sources[key] = {row[0] for row in rows}
+        return sources
+
+    def reset_seen(self):
+        with self.connection:
+            self.connection.execute("DELETE FROM seen_flats")
+            self.connection.execute("DELETE FROM sent_dedup_keys")
+
+    def evict_seen(self, max_age: timedelta, now: Optional[datetime] = None) -> int:
+        cutoff = ((now or datetime.now()) - max_age).isoformat()
+        with self.connection:
+            cursor = self.connection.execute(
+                "DELETE FROM seen_flats WHERE first_seen < ?", (cutoff,)
+            )
+            self.connection.execute(
+                "DELETE FROM sent_dedup_keys WHERE first_seen < ?", (cutoff,)
+            )
+        return cursor.rowcount
+
+    def save_snapshots(self, flats: List[FlatDetails]):
+        now = datetime.now().isoformat()
+        with self.connection:
+            self.connection.executemany(
+                "INSERT OR REPLACE INTO flat_snapshots (id, source, data, updated_at) "
+                "VALUES (?, ?, ?, ?)",
+                [
+                    (flat.id, flat.source, json.dumps(flat.to_dict()), now)
+                    for flat in flats
+                ],
+            )
+
+    def snapshot(self, flat_id: str) -> Optional[FlatDetails]:
+        row = self.connection.execute(
+            "SELECT data FROM flat_snapshots WHERE id = ?", (flat_id,)
+        ).fetchone()
+        return FlatDetails.from_dict(json.loads(row[0])) if row else None
+
+    def record_notifications(self, flats: List[FlatDetails], sent_at: Optional[datetime] = None):
+        sent_at = (sent_at or datetime.now()).isoformat()
+        with self.connection:
+            self.connection.executemany(
+                "INSERT INTO notifications (flat_id, source, sent_at) VALUES (?, ?, ?)",
+                [(flat.id, flat.source, sent_at) for flat in flats],
+            )
+
+    def close(self):
+        if self.connection is not None:
+            self.connection.close()
+            self.connection = None
+
+
+def create_store(database_url: Optional[str]) -> Store:
+    """SqliteStore for "sqlite:///path/to/flats.db", JsonStore without a URL."""
+    if not database_url:
+        return JsonStore()
+    return SqliteStore(database_url[len("sqlite:///"):])
@@ -0,0 +1,1070 @@
+#!/usr/bin/env python3
+"""Tests of FlatMonitor's diff, filter and notify logic without real websites.
+
+MockScraper stands in for the housing websites and a recording notifier for
+Telegram and Discord, so nothing leaves the machine. Each test sets up its
+own monitor, config and temporary directory.
+
+Run all tests with `python3 test_pipeline.py` or a single one with
+`python3 -m unittest test_pipeline.CycleTest`.
+"""
+
+import asyncio
+import json
+import logging
+import os
+import sys
+import tempfile
+import time
+import unittest
+from datetime import datetime, timedelta, timezone
+from types import SimpleNamespace
+from unittest import mock
+
+from bs4 import BeautifulSoup
+
+import bot
+import scrapers.cache
+import scrapers.session
+
+from bot import (
+    MAX_LIST_LIMIT,
+    Config,
+    FlatMonitor,
+    MessageFormatter,
+    TelegramNotifier,
+    clamp_list_limit,
+    to_local,
+    wbs_route,
+)
+from notifiers import Notifier, html_to_markdown
+from scrapers import (
+    FilterCriteria,
+    FlatDetails,
+    GenericScraper,
+    HighTrafficError,
+    StadtUndLandScraper,
+    StandardFields,
+    WebsiteUnavailableError,
+    drop_cross_source_duplicates,
+)
+from scrapers.base import parse_floor, select_first
+from scrapers.degewo import PRICE_SELECTORS, TITLE_SELECTORS
+from scrapers.mock import MockScraper
+from scrapers.session import request_headers, session_headers
+from telegram.error import TelegramError
+
+logging.basicConfig(
+    format="%(asctime)s - %(name)s - %(levelname)s - %(message)s",
+    level=logging.WARNING
+)
+
+
+class FakeMessage:
+    """Stands in for the Telegram message of a command, recording the replies."""
+
+    text = "/command"
+
+    def __init__(self):
+        self.replies = []
+
+    async def reply_text(self, text, **kwargs):
+        self.replies.append(text)
+
+
+class FlakyMessage(FakeMessage):
+    """A command message whose n-th reply (counting from 0) fails to send."""
+
+    def __init__(self, fail_at: int):
+        super().__init__()
+        self.fail_at = fail_at
+        self.calls = 0
+
+    async def reply_text(self, text, **kwargs):
+        self.calls += 1
+        if self.calls - 1 == self.fail_at:
+            raise TelegramError("mock send failure")
+        await super().reply_text(text, **kwargs)
+
+
+class SlowScraper(MockScraper):
+    """A MockScraper whose website takes delay seconds to answer."""
+
+    def __init__(self, name: str, delay: float, flats=None):
+        super().__init__(name, flats)
+        self.delay = delay
+
+    async def fetch_flats(self, url=None):
+        await asyncio.sleep(self.delay)
+        return await super().fetch_flats(url)
+
+
+class FakeResponse:
+    charset = "utf-8"
+
+    def __init__(self, status: int = 200, body: bytes = b"<html><body>No flats</body></html>"):
+        self.status = status
+        self.body = body
+
+    async def read(self):
+        return self.body
+
+    async def __aenter__(self):
+        return self
+
+    async def __aexit__(self, *exc_info):
+        return False
+
+
+class FakeSession:
+    """Stands in for the aiohttp session, recording the request arguments.
+    Responses have the given statuses in turn, the last one repeating."""
+
+    def __init__(self, body: bytes = FakeResponse().body, statuses=(200,)):
+        self.body = body
+        self.statuses = list(statuses)
+        self.requests = []
+        self.methods = []
+
+    def request(self, method, url, **kwargs):
+        self.requests.append(kwargs)
+        self.methods.append((method, url))
+        status = self.statuses.pop(0) if len(self.statuses) > 1 else self.statuses[0]
+        return FakeResponse(status, self.body)
+
+
+class RecordingNotifier(Notifier):
+    name = "recording"
+
+    def __init__(self):
+        self.broadcasts = []
+        self.admin_messages = []
+
+    async def broadcast(self, text, silent=False):
+        self.broadcasts.append(text)
+        return []
+
+    async def send_admin(self, text, silent=False):
+        self.admin_messages.append(text)
+
+
+class WbsRecordingNotifier(RecordingNotifier):
+    """Records WBS flats sent to the separate WBS targets apart from the broadcasts."""
+
+    def __init__(self):
+        super().__init__()
+        self.wbs_broadcasts = []
+
+    async def broadcast_wbs(self, text, silent=False):
+        self.wbs_broadcasts.append(text)
+        return []
+
+
+def make_flat(flat_id: str, rooms: str = "2", **fields) -> FlatDetails:
+    return FlatDetails(
+        id=flat_id,
+        title=f"Flat {flat_id}",
+        link=f"https://example.org/flats/{flat_id}",
+        details={StandardFields.ROOMS: rooms, StandardFields.ADDRESS: "Teststr. 1, 10115 Berlin"},
+        wbs_required=False,
+        source="Mock",
+        **fields,
+    )
+
+
+def write_config(tmp: str, name: str, **settings) -> str:
+    """Write a config with the required keys and the given settings, return its path."""
+    path = os.path.join(tmp, f"{name}.json")
+    with open(path, "w") as f:
+        json.dump({"BOT_TOKEN": "123456:TEST", "CHAT_ID": "1", "PRIVATE_CHAT_ID": "2", **settings}, f)
+    return path
+
+
+def load_config(tmp: str, name: str, **settings) -> Config:
+    """Load a config with the required keys and the given settings."""
+    return Config(write_config(tmp, name, **settings))
+
+
+def command_update(chat_id: int = 1, username=None, message=None) -> SimpleNamespace:
+    """A Telegram update carrying a command from user 42 in the given chat."""
+    return SimpleNamespace(
+        effective_chat=SimpleNamespace(id=chat_id, username=username),
+        effective_user=SimpleNamespace(id=42),
+        message=message or FakeMessage(),
+    )
+
+
+class MonitorTestCase(unittest.IsolatedAsyncioTestCase):
+    """Runs a FlatMonitor with one MockScraper listing flat "a" and settings on top of
+    the required ones. A throwaway database keeps the real seen flats cache untouched."""
+
+    settings: dict = {}
+
+    async def asyncSetUp(self):
+        tmp = tempfile.TemporaryDirectory()
+        self.addCleanup(tmp.cleanup)
+        self.tmp = tmp.name
+        settings = {
+            "MIN_ROOMS": 2,
+            "DATABASE_URL": f"sqlite:///{os.path.join(self.tmp, 'flats.db')}",
+            **self.settings,
+        }
+        self.scraper = MockScraper("Mock", [make_flat("a")])
+        self.monitor = FlatMonitor(load_config(self.tmp, "config", **settings), scrapers=[self.scraper])
+        self.addCleanup(self.monitor.store.close)
+        self.notifier = RecordingNotifier()
+        self.monitor.notifiers = [self.notifier]
+
+    async def next_cycle(self):
+        """Run a check with every scraper due, without waiting for MONITOR_INTERVAL."""
+        self.monitor.next_due.clear()
+        return await self.monitor.check_for_new_flats()
+
+
+class CycleTest(MonitorTestCase):
+    settings = {"MESSAGE_FOOTER": "Contact: flats@example.org"}
+
+    async def test_only_new_matching_flats_are_notified(self):
+        first = await self.next_cycle()
+        # A new matching flat and one that is too small for MIN_ROOMS
+        self.scraper.flats += [make_flat("b", "3"), make_flat("c", "1")]
+        second = await self.next_cycle()
+        self.assertEqual([flat.id for flat in first], ["a"])
+        self.assertEqual([flat.id for flat in second], ["b"])
+
+    async def test_notifications_carry_footer(self):
+        await self.next_cycle()
+        self.assertEqual(len(self.notifier.broadcasts), 1)
+        self.assertIn("Contact: flats@example.org", self.notifier.broadcasts[0])
+
+    async def test_republished_flat_is_not_notified(self):
+        await self.next_cycle()
+        # The same listing under a new ID
+        repost = make_flat("a2")
+        repost.title, repost.link = "Flat a", "https://example.org/flats/a"
+        self.scraper.flats = [repost]
+        self.assertEqual(await self.next_cycle(), [])
+
+    async def test_scan_checks_scrapers_that_are_not_due(self):
+        await self.monitor.check_for_new_flats()
+        self.scraper.flats.append(make_flat("d"))
+        update = command_update()
+        await self.monitor.handle_scan_command(update, SimpleNamespace(args=[]))
+        self.assertEqual(len(self.notifier.broadcasts), 2)
+        self.assertEqual(update.message.replies[-1], "✅ Scan done, new matching flats: 1")
+
+
+class DryRunTest(MonitorTestCase):
+    settings = {"DRY_RUN": True}
+
+    async def test_notifications_are_only_logged(self):
+        self.monitor.notifiers = [TelegramNotifier(self.monitor)]
+        self.monitor._send_with_fallback = mock.AsyncMock()
+        with self.assertLogs("bot", logging.INFO) as logs:
+            notified = await self.next_cycle()
+        self.assertEqual([flat.id for flat in notified], ["a"])
+        self.monitor._send_with_fallback.assert_not_awaited()
+        dry_runs = [line for line in logs.output if "[dry run] Message to chat 1" in line]
+        self.assertEqual(len(dry_runs), 1)
+        self.assertIn("Flat a", dry_runs[0])
+
+
+class BroadcastTest(MonitorTestCase):
+    settings = {"CHAT_ID": ["1", "-1003", "@flatgroup"]}
+
+    async def test_flats_are_sent_to_every_chat(self):
+        self.monitor.notifiers = [TelegramNotifier(self.monitor)]
+        self.monitor._send_with_fallback = mock.AsyncMock()
+        await self.next_cycle()
+        chats = [call.args[0] for call in self.monitor._send_with_fallback.await_args_list]
+        self.assertEqual(chats, ["1", "-1003", "@flatgroup"])
+
+
+class BatchTest(MonitorTestCase):
+    settings = {"BATCH_NOTIFICATIONS": True, "BATCH_SIZE": 3}
+
+    async def test_batch_produces_fewer_messages_than_flats(self):
+        flats = [make_flat(flat_id) for flat_id in "abcde"]
+        await self.monitor.send_update(flats)
+        # Up to BATCH_SIZE flats per message
+        self.assertEqual(len(self.notifier.broadcasts), 2)
+        for flat in flats:
+            self.assertEqual(sum(flat.title in text for text in self.notifier.broadcasts), 1)
+
+    def test_batches_respect_the_message_length(self):
+        messages = ["x" * 30] * 4
+        batches = self.monitor.formatter.batch_messages(messages, 10, max_length=70)
+        # Two flats and their separator fit, a flat is never cut
+        self.assertEqual(batches, ["x" * 30 + "\n" + "x" * 30] * 2)
+
+
+class PendingBufferTest(MonitorTestCase):
+    settings = {"MIN_BATCH_SIZE": 3, "MAX_HOLD_SECONDS": 600}
+
+    async def asyncSetUp(self):
+        await super().asyncSetUp()
+        self.start = datetime(2025, 3, 3, 12, 0)
+
+    def test_released_once_min_batch_size_is_reached(self):
+        self.assertEqual(self.monitor._release_pending([make_flat("a"), make_flat("b")], self.start), [])
+        # The same flat again doesn't count twice
+        self.assertEqual(self.monitor._release_pending([make_flat("b")], self.start), [])
+        released = self.monitor._release_pending([make_flat("c")], self.start + timedelta(seconds=5))
+        self.assertEqual([flat.id for flat in released], ["a", "b", "c"])
+        self.assertEqual(self.monitor.pending_flats, [])
+
+    def test_released_after_max_hold(self):
+        self.assertEqual(self.monitor._release_pending([make_flat("a")], self.start), [])
+        held = self.monitor._release_pending([], self.start + timedelta(seconds=599))
+        self.assertEqual(held, [])
+        released = self.monitor._release_pending([], self.start + timedelta(seconds=600))
+        self.assertEqual([flat.id for flat in released], ["a"])
+
+
+class QuietHoursTest(MonitorTestCase):
+    settings = {"QUIET_HOURS_START": 22, "QUIET_HOURS_END": 7}
+
+    def at(self, day: int, hour: int, minute: int = 0) -> datetime:
+        """A moment in March 2025, Berlin time."""
+        return datetime(2025, 3, day, hour, minute, tzinfo=self.monitor.config.timezone)
+
+    def test_window_wraps_past_midnight(self):
+        quiet = [self.monitor._is_quiet_hours(self.at(3, hour)) for hour in (21, 22, 23, 0, 6, 7, 12)]
+        self.assertEqual(quiet, [False, True, True, True, True, False, False])
+
+    def test_notification_during_quiet_hours_is_deferred(self):
+        self.assertEqual(self.monitor._release_pending([make_flat("a")], self.at(3, 23, 30)), [])
+        # Still held after midnight
+        self.assertEqual(self.monitor._release_pending([], self.at(4, 3)), [])
+        released = self.monitor._release_pending([], self.at(4, 7, 5))
+        self.assertEqual([flat.id for flat in released], ["a"])
+
+
+class WbsPolicyTest(MonitorTestCase):
+    settings = {"WBS_POLICY": "separate_chat", "WBS_CHAT_ID": "-1003"}
+
+    async def asyncSetUp(self):
+        await super().asyncSetUp()
+        self.notifier = WbsRecordingNotifier()
+        self.monitor.notifiers = [self.notifier]
+        wbs_flat = make_flat("w")
+        wbs_flat.wbs_required = True
+        self.scraper.flats.append(wbs_flat)
+
+    async def test_separate_chat_sends_wbs_flats_to_wbs_targets(self):
+        notified = await self.next_cycle()
+        self.assertEqual(sorted(flat.id for flat in notified), ["a", "w"])
+        self.assertEqual(len(self.notifier.broadcasts), 1)
+        self.assertIn("Flat a", self.notifier.broadcasts[0])
+        self.assertEqual(len(self.notifier.wbs_broadcasts), 1)
+        self.assertIn("Flat w", self.notifier.wbs_broadcasts[0])
+
+    async def test_telegram_wbs_chats(self):
+        self.monitor.subscribers = ["555"]
+        self.assertEqual(self.monitor._wbs_chats(), ["-1003", "555"])
+
+    async def test_excluded_flats_are_not_counted(self):
+        self.monitor.config.wbs_policy = "exclude"
+        self.monitor.criteria.allow_wbs = False
+        await self.monitor.send_update(self.scraper.flats)
+        self.assertEqual([flat.id for _, flat in self.monitor.recent_flats], ["a"])
+        self.assertEqual(self.notifier.wbs_broadcasts, [])
+
+
+class PriceChangeTest(MonitorTestCase):
+    """Flat "a" listed for 800 € warm, then again with another rent."""
+
+    async def asyncSetUp(self):
+        await super().asyncSetUp()
+        await self.relist("800 €")
+        self.notifier.broadcasts.clear()
+
+    async def relist(self, rent: str):
+        flat = make_flat("a")
+        flat.details[StandardFields.RENT_WARM] = rent
+        self.scraper.flats = [flat]
+        await self.next_cycle()
+
+    async def test_rent_increase(self):
+        await self.relist("850 €")
+        self.assertEqual(len(self.notifier.broadcasts), 1)
+        self.assertIn("📈", self.notifier.broadcasts[0])
+        self.assertIn("800 € → <b>850 €</b> (+50 €)", self.notifier.broadcasts[0])
+
+    async def test_rent_decrease(self):
+        await self.relist("750 €")
+        self.assertEqual(len(self.notifier.broadcasts), 1)
+        self.assertIn("📉", self.notifier.broadcasts[0])
+        self.assertIn("800 € → <b>750 €</b> (-50 €)", self.notifier.broadcasts[0])
+
+    async def test_unparseable_rent_keeps_last_known(self):
+        await self.relist("auf Anfrage")
+        self.assertEqual(self.monitor.known_rents["a"], 800)
+        # Back to the old rent: not a change
+        await self.relist("800 €")
+        self.assertEqual(self.notifier.broadcasts, [])
+
+
+class RestartTest(MonitorTestCase):
+    def start(self, database: str, flats) -> FlatMonitor:
+        """A monitor sharing the STATE_FILE of the others, with its own seen flats store."""
+        config = load_config(
+            self.tmp, database,
+            MIN_ROOMS=2,
+            DATABASE_URL=f"sqlite:///{os.path.join(self.tmp, database + '.db')}",
+            STATE_FILE=os.path.join(self.tmp, "state.json"),
+        )
+        monitor = FlatMonitor(config, scrapers=[MockScraper("Mock", flats)])
+        self.addCleanup(monitor.store.close)
+        monitor.notifiers = [RecordingNotifier()]
+        return monitor
+
+    async def test_current_flats_survive_restart(self):
+        before = self.start("before", [make_flat("a")])
+        await before.check_for_new_flats()
+        before.save_current_flats()
+        # After a reboot the seen flats in /dev/shm are gone, the state file is not
+        after = self.start("after", [make_flat("a"), make_flat("b")])
+        self.assertEqual([flat.to_dict() for flat in after.current_flats],
+                         [flat.to_dict() for flat in before.current_flats])
+        notified = await after.check_for_new_flats()
+        self.assertEqual([flat.id for flat in notified], ["b"])
+
+
+class ConcurrentFetchTest(MonitorTestCase):
+    async def test_check_takes_as_long_as_the_slowest_scraper(self):
+        slow = SlowScraper("Slow", 0.4, [make_flat("s")])
+        fast = SlowScraper("Fast", 0.2, [make_flat("f")])
+        self.monitor.scrapers = [slow, fast]
+        started = time.monotonic()
+        flats = await self.monitor.fetch_all_flats()
+        elapsed = time.monotonic() - started
+        self.assertEqual(sorted(flat.id for flat in flats), ["f", "s"])
+        # One after the other would take 0.6s
+        self.assertLess(elapsed, 0.55)
+
+    async def test_failing_scraper_does_not_stop_the_others(self):
+        failing = SlowScraper("Failing", 0.1)
+        failing.fail_next(RuntimeError("mock outage"))
+        self.monitor.scrapers = [failing, self.scraper]
+        flats = await self.monitor.fetch_all_flats()
+        self.assertEqual([flat.id for flat in flats], ["a"])
+        self.assertEqual(self.monitor.website_statuses["Mock"], "Available")
+
+
+class IntervalTest(MonitorTestCase):
+    settings = {"SCRAPER_INTERVALS": {"Slow": 600}}
+
+    async def asyncSetUp(self):
+        await super().asyncSetUp()
+        self.slow = MockScraper("Slow", [make_flat("s")])
+        self.monitor.scrapers.append(self.slow)
+
+    async def test_scraper_is_skipped_until_due(self):
+        await self.monitor.check_for_new_flats()
+        # A MONITOR_INTERVAL later only the Mock scraper is due again
+        self.monitor.next_due["Mock"] = datetime.now()
+        await self.monitor.check_for_new_flats()
+        self.assertEqual((self.scraper.fetch_count, self.slow.fetch_count), (2, 1))
+
+    def test_health_window_follows_longest_interval(self):
+        self.monitor.last_successful_cycle = datetime.now() - timedelta(seconds=300)
+        self.assertTrue(self.monitor.is_healthy())
+        self.monitor.last_successful_cycle = datetime.now() - timedelta(seconds=1300)
+        self.assertFalse(self.monitor.is_healthy())
+
+
+class ScraperPacingTest(MonitorTestCase):
+    settings = {"MAX_CONCURRENT_SCRAPERS": 2}
+
+    async def fetch_duration(self, scrapers) -> float:
+        self.monitor.scrapers = scrapers
+        started = time.monotonic()
+        await self.monitor.fetch_all_flats()
+        return time.monotonic() - started
+
+    async def test_concurrency_limit(self):
+        scrapers = [SlowScraper(name, 0.2) for name in ("One", "Two", "Three")]
+        # Two at a time: the third starts when one of the first two is done
+        elapsed = await self.fetch_duration(scrapers)
+        self.assertGreaterEqual(elapsed, 0.4)
+        self.assertLess(elapsed, 0.55)
+
+    async def test_delay_between_scrapers(self):
+        self.monitor.config.inter_scraper_delay_ms = 200
+        scrapers = [SlowScraper(name, 0) for name in ("One", "Two", "Three")]
+        self.assertGreaterEqual(await self.fetch_duration(scrapers), 0.4)
+
+
+class DedupTtlTest(MonitorTestCase):
+    """With the SQLite store (DATABASE_URL)."""
+
+    settings = {"DEDUP_TTL_DAYS": 30}
+
+    async def relist(self):
+        """Take flat "a" offline for a cycle and list it again, return what was notified."""
+        listed = self.scraper.flats
+        self.scraper.flats = []
+        await self.next_cycle()
+        self.scraper.flats = listed
+        return [flat.id for flat in await self.next_cycle()]
+
+    async def test_seen_flats_expire(self):
+        await self.next_cycle()
+        self.monitor.evict_expired_flats(datetime.now() + timedelta(days=29))
+        self.assertTrue(self.monitor.store.is_seen("a"))
+        self.monitor.evict_expired_flats(datetime.now() + timedelta(days=31))
+        self.assertFalse(self.monitor.store.is_seen("a"))
+
+    async def test_same_flat_from_another_source_in_later_check(self):
+        self.scraper.flats[0].details[StandardFields.RENT_WARM] = "850 €"
+        await self.next_cycle()
+        # Another website lists flat "a" under its own ID a check later
+        other = make_flat("x")
+        other.details = dict(self.scraper.flats[0].details)
+        other.source = "Other"
+        self.monitor.scrapers.append(MockScraper("Other", [other]))
+        self.assertEqual([flat.id for flat in await self.next_cycle()], [])
+        # The dedup keys expire with the seen IDs
+        self.monitor.evict_expired_flats(datetime.now() + timedelta(days=31))
+        self.scraper.flats = []
+        self.monitor.current_flats = []
+        self.assertEqual([flat.id for flat in await self.next_cycle()], ["x"])
+
+    async def test_relisted_flat_is_reported_after_expiry(self):
+        await self.next_cycle()
+        self.assertEqual(await self.relist(), [])
+        self.monitor.evict_expired_flats(datetime.now() + timedelta(days=31))
+        self.assertEqual(await self.relist(), ["a"])
+
+
+class JsonDedupTtlTest(DedupTtlTest):
+    """The same with the default JsonStore, its cache file in the temporary directory."""
+
+    async def asyncSetUp(self):
+        tmp = tempfile.TemporaryDirectory()
+        self.addCleanup(tmp.cleanup)
+        cache_file = mock.patch.object(
+            scrapers.cache, "_SEEN_FLATS_CACHE_FILE", os.path.join(tmp.name, "seen.json")
+        )
+        cache_file.start()
+        self.addCleanup(cache_file.stop)
+        self.settings = {**self.settings, "DATABASE_URL": ""}
+        await super().asyncSetUp()
+        self.monitor.store.reset_seen()
+
+
+class ReliabilityTest(MonitorTestCase):
+    async def test_errors_lower_and_successes_raise_reliability(self):
+        # No score before the first fetch
+        self.assertIsNone(self.scraper.reliability())
+        await self.monitor._fetch_from_scraper(self.scraper)
+        self.assertEqual(self.scraper.reliability(), 100)
+        for _ in range(3):
+            # Skip the backoff, fetches during it don't count
+            self.scraper._reset_backoff()
+            self.scraper.fail_next(RuntimeError("mock outage"))
+            await self.monitor._fetch_from_scraper(self.scraper)
+        failing = self.scraper.reliability()
+        self.scraper._reset_backoff()
+        for _ in range(3):
+            await self.monitor._fetch_from_scraper(self.scraper)
+        recovered = self.scraper.reliability()
+        self.assertLess(failing, 60)
+        self.assertTrue(failing < recovered < 100)
+
+
+class RecoveryTest(MonitorTestCase):
+    async def fetch(self, fail: bool = False):
+        # A failure starts the backoff, skip it so every fetch reaches the scraper
+        self.scraper._reset_backoff()
+        if fail:
+            self.scraper.fail_next(RuntimeError("mock outage"))
+        await self.monitor._fetch_from_scraper(self.scraper)
+
+    async def test_recovery_is_notified_once_after_errors(self):
+        await self.fetch(fail=True)
+        self.assertEqual(len(self.notifier.admin_messages), 1)
+        await self.fetch()
+        await self.fetch()
+        self.assertEqual(self.notifier.admin_messages[1:], ["✅ <b>Mock recovered</b>"])
+
+    async def test_no_recovery_without_prior_error(self):
+        await self.fetch()
+        self.assertEqual(self.notifier.admin_messages, [])
+
+
+class WelcomeTest(MonitorTestCase):
+    async def test_welcome_goes_to_private_chat_by_default(self):
+        await self.monitor.send_welcome()
+        self.assertEqual(len(self.notifier.admin_messages), 1)
+        self.assertEqual(self.notifier.broadcasts, [])
+
+    async def test_welcome_broadcast_when_not_private_only(self):
+        self.monitor.config.welcome_to_private_only = False
+        await self.monitor.send_welcome()
+        self.assertEqual(len(self.notifier.broadcasts), 1)
+
+
+class CommandTest(MonitorTestCase):
+    async def test_whoami_answers_in_any_chat(self):
+        update = command_update(chat_id=-100777, username="flatgroup")
+        await self.monitor.handle_whoami_command(update, SimpleNamespace(args=[]))
+        self.assertEqual(
+            update.message.replies[-1], "Chat ID: -100777\nChat username: @flatgroup\nUser ID: 42"
+        )
+
+    async def test_test_command_keeps_seen_flats(self):
+        await self.next_cycle()
+        update = command_update()
+        await self.monitor.test_command(update, SimpleNamespace(args=[]))
+        self.assertTrue(self.monitor.store.is_seen("a"))
+        self.assertIn("Flat a", update.message.replies[-1])
+
+    async def test_list_continues_after_failed_send(self):
+        self.scraper.flats += [make_flat("b"), make_flat("d")]
+        await self.next_cycle()
+        # The header is reply 0; the second flat fails, the third still has to arrive
+        update = command_update(message=FlakyMessage(fail_at=2))
+        await self.monitor.handle_list_command(update, SimpleNamespace(args=["3"]))
+        replies = update.message.replies
+        self.assertEqual(len(replies), 3)
+        self.assertIn("Flat d", replies[-1])
+
+    async def test_retry_usage_send_failure_is_handled(self):
+        # PRIVATE_CHAT_ID is 2
+        update = command_update(chat_id=2, message=FlakyMessage(fail_at=0))
+        await self.monitor.handle_retry_command(update, SimpleNamespace(args=[]))
+        self.assertEqual(update.message.calls, 1)
+
+    async def test_admin_commands_check_authorized_users(self):
+        self.monitor.config.authorized_user_ids = ["7"]
+        self.monitor.reload_config = mock.Mock(return_value=([], []))
+        self.scraper._update_backoff()
+        # User 42 in the private chat, but not in AUTHORIZED_USER_IDS
+        for handler, args in ((self.monitor.handle_reload_command, []),
+                              (self.monitor.handle_retry_command, ["Mock"])):
+            update = command_update(chat_id=2)
+            await handler(update, SimpleNamespace(args=args))
+            self.assertEqual(update.message.replies, ["Sorry, you are not allowed to use this bot's commands."])
+        self.monitor.reload_config.assert_not_called()
+        self.assertTrue(self.scraper.in_backoff)
+
+    def test_list_limit_is_clamped(self):
+        results = [clamp_list_limit(value, 5) for value in (None, "10", "0", "-3", "1000")]
+        self.assertEqual(results, [5, 10, 1, 1, MAX_LIST_LIMIT])
+        with self.assertRaises(ValueError):
+            clamp_list_limit("ten", 5)
+
+
+class RequestTest(MonitorTestCase):
+    settings = {"SCRAPER_COOKIES": {"Mock": {"consent": "accepted"}}}
+
+    async def test_requests_carry_scraper_cookies(self):
+        session = FakeSession()
+        await self.scraper._make_request(session, url="https://example.org/flats")
+        self.assertEqual(session.requests[0]["headers"].get("Cookie"), "consent=accepted")
+
+    async def request(self, session):
+        """Request with a fresh scraper, without waiting between retries."""
+        with mock.patch("scrapers.base.asyncio.sleep", new=mock.AsyncMock()):
+            return await MockScraper("Mock")._make_request(session, url="https://example.org/flats")
+
+    async def test_server_errors_are_retried(self):
+        for status in (500, 502, 504):
+            with self.subTest(status=status):
+                session = FakeSession(statuses=[status, 200])
+                success, _ = await self.request(session)
+                self.assertTrue(success)
+                self.assertEqual(len(session.requests), 2)
+                failing = FakeSession(statuses=[status])
+                with self.assertRaises(WebsiteUnavailableError):
+                    await self.request(failing)
+                # All max_retries attempts
+                self.assertEqual(len(failing.requests), 3)
+
+    async def test_overload_is_not_retried(self):
+        for status in (503, 429):
+            with self.subTest(status=status):
+                session = FakeSession(statuses=[status, 200])
+                with self.assertRaises(HighTrafficError):
+                    await self.request(session)
+                self.assertEqual(len(session.requests), 1)
+
+    async def test_retry_delays_are_jittered(self):
+        scraper = MockScraper("Mock")
+        scraper.rng.seed(1)
+        scraper.max_retries = 5
+        scraper.max_backoff_time = 5
+        session = FakeSession(statuses=[500, 500, 500, 500, 200])
+        with mock.patch("scrapers.base.asyncio.sleep", new=mock.AsyncMock()) as sleep:
+            await scraper._make_request(session, url="https://example.org/flats")
+        delays = [call.args[0] for call in sleep.await_args_list]
+        self.assertEqual(len(delays), 4)
+        for attempt, delay in enumerate(delays):
+            # Full jitter up to 1, 2, 4 seconds, then capped by max_backoff_time
+            self.assertTrue(0 <= delay <= min(2**attempt, 5), (attempt, delay))
+        self.assertNotEqual(delays, [1, 2, 4, 5])
+
+    async def test_fetch_all_passes_each_url(self):
+        fetched = []
+
+        class PagedScraper(MockScraper):
+            async def fetch_flats(self, url=None):
+                fetched.append((url, self.url))
+                return await super().fetch_flats(url)
+
+        scraper = PagedScraper("Paged", [make_flat("a")])
+        scraper.set_urls(["https://example.org/page1", "https://example.org/page2"])
+        await scraper.fetch_all()
+        # self.url stays the first page, so concurrent fetches don't mix pages
+        self.assertEqual(fetched, [
+            ("https://example.org/page1", "https://example.org/page1"),
+            ("https://example.org/page2", "https://example.org/page1"),
+        ])
+
+    async def test_scraper_urls_ignored_for_api_scrapers(self):
+        scraper = StadtUndLandScraper("https://stadtundland.de/wohnungssuche")
+        config = load_config(
+            self.tmp, "urls",
+            DATABASE_URL=f"sqlite:///{os.path.join(self.tmp, 'urls.db')}",
+            SCRAPER_URLS={"StadtUndLand": ["https://example.org/flats"]},
+        )
+        monitor = FlatMonitor(config, scrapers=[scraper])
+        self.addCleanup(monitor.store.close)
+        self.assertEqual(scraper.urls, ["https://stadtundland.de/wohnungssuche"])
+
+    async def test_stadtundland_posts_through_make_request(self):
+        scraper = StadtUndLandScraper("https://stadtundland.de/wohnungssuche")
+        scraper.cookies = {"consent": "accepted"}
+        listing = {
+            "headline": "2-Zimmer-Wohnung",
+            "details": {"immoNumber": "SL-1", "rooms": 2, "livingSpace": 54},
+            "address": {"street": "Teststr.", "house_number": "1"},
+            "costs": {"totalRent": 700},
+        }
+        session = FakeSession(json.dumps({"data": [listing]}).encode())
+        with mock.patch("scrapers.stadtundland.get_session", new=mock.AsyncMock(return_value=session)):
+            flats = await scraper.fetch_flats()
+        self.assertEqual([flat.id for flat in flats], ["SL-1"])
+        self.assertEqual(session.methods[0][0], "POST")
+        self.assertEqual(session.requests[0]["json"], {"offset": 0, "cat": "wohnung"})
+        self.assertEqual(session.requests[0]["headers"].get("Cookie"), "consent=accepted")
+
+    def test_user_agent_rotation_keeps_own_user_agent(self):
+        with mock.patch.object(scrapers.session, "_user_agents", ["Rotated/1.0"]):
+            self.assertEqual(request_headers({})["User-Agent"], "Rotated/1.0")
+            self.assertEqual(request_headers({"User-Agent": "Own/2.0"})["User-Agent"], "Own/2.0")
+
+    def test_german_accept_language(self):
+        # German pages keep the labels the parsers rely on ("Zimmer", not "rooms")
+        self.assertEqual(session_headers().get("Accept-Language"), "de-DE,de;q=0.9")
+
+
+class ShutdownTest(unittest.IsolatedAsyncioTestCase):
+    async def test_store_is_closed_when_startup_fails(self):
+        with mock.patch("bot.Config"), mock.patch("bot.FlatMonitor") as monitor_class, \
+                mock.patch("bot.Application") as application, \
+                mock.patch("bot.close_session", new=mock.AsyncMock()):
+            application.builder.side_effect = RuntimeError("mock startup failure")
+            await bot.main()
+        monitor_class.return_value.store.close.assert_called_once()
+
+
+class ParserTest(unittest.IsolatedAsyncioTestCase):
+    """Scrapers on small HTML snippets."""
+
+    def test_fallback_selectors(self):
+        soup = BeautifulSoup(
+            '<article><h3>Neubau am Park</h3><span class="price">700 €</span></article>',
+            "html.parser",
+        )
+        # Neither h2.article__title nor .article__price-tag match, the fallbacks do
+        self.assertEqual(select_first(soup, TITLE_SELECTORS).get_text(), "Neubau am Park")
+        self.assertEqual(select_first(soup, PRICE_SELECTORS).get_text(), "700 €")
+        preferred = BeautifulSoup(
+            '<article><h2 class="article__title">Altbau</h2><h3>Neubau am Park</h3></article>',
+            "html.parser",
+        )
+        self.assertEqual(select_first(preferred, TITLE_SELECTORS).get_text(), "Altbau")
+
+    async def test_generic_scraper(self):
+        scraper = GenericScraper.from_config({
+            "name": "Example",
+            "url": "https://example.org/wohnungen/",
+            "item_selector": "div.flat",
+            "details": {"rooms": ".rooms", "rent_warm": ".rent"},
+            "listed_at_selector": "time",
+        })
+        page = """<html><body>
+            <div class="flat">
+              <h3>Altbau mit Balkon</h3>
+              <a href="details/17">Details</a>
+              <span class="rooms">2</span> <span class="rent">850 €</span>
+              <time datetime="2025-03-01T09:30:00">1. März</time>
+            </div>
+            <div class="flat"><h4>Ohne Link</h4><span class="rooms">1</span></div>
+        </body></html>"""
+        session = FakeSession(page.encode())
+        with mock.patch("scrapers.generic.get_session", new=mock.AsyncMock(return_value=session)):
+            flats = await scraper.fetch_all()
+        self.assertEqual(len(flats), 2)
+        flat, unlinked = flats
+        self.assertEqual(flat.title, "Altbau mit Balkon")
+        # Relative links are joined with the page URL and serve as ID
+        self.assertEqual(flat.link, "https://example.org/wohnungen/details/17")
+        self.assertEqual(flat.id, flat.link)
+        self.assertEqual(flat.details[StandardFields.ROOMS], "2")
+        self.assertEqual(flat.details[StandardFields.RENT_WARM], "850 €")
+        self.assertEqual(flat.listed_at, datetime(2025, 3, 1, 9, 30))
+        self.assertEqual(flat.source, "Example")
+        self.assertEqual((unlinked.title, unlinked.link, unlinked.listed_at), ("Ohne Link", None, None))
+
+
+class FilterTest(unittest.TestCase):
+    def test_cross_source_duplicates(self):
+        def listing(flat_id, source, address, rent):
+            return FlatDetails(
+                id=flat_id,
+                title=f"Flat {flat_id}",
+                link=f"https://example.org/{flat_id}",
+                details={
+                    StandardFields.ADDRESS: address,
+                    StandardFields.ROOMS: "2",
+                    StandardFields.RENT_WARM: rent,
+                },
+                wbs_required=False,
+                source=source,
+            )
+
+        flats = [
+            listing("x1", "Degewo", "Müllerstraße 12 A, 13353 Berlin", "850,00 €"),
+            # Same flat, formatted differently by another portal
+            listing("x2", "InBerlinWohnen", "Müllerstr. 12a", "850 €"),
+            # Same address and rooms, but another rent: kept
+            listing("x3", "Gesobau", "Müllerstr. 12a", "910 €"),
+            # Next house: kept
+            listing("x4", "Gewobag", "Müllerstraße 14, 13353 Berlin", "850 €"),
+        ]
+        ids = [flat.id for flat in drop_cross_source_duplicates(flats)]
+        self.assertEqual(ids, ["x1", "x3", "x4"])
+
+    def test_content_hash(self):
+        flat, same, cheaper = make_flat("a"), make_flat("a"), make_flat("a")
+        flat.details[StandardFields.RENT_WARM] = "850 €"
+        # Details in another order
+        same.details = dict(reversed([*flat.details.items()]))
+        cheaper.details = {**flat.details, StandardFields.RENT_WARM: "800 €"}
+        self.assertEqual(flat.content_hash(), same.content_hash())
+        self.assertNotEqual(flat.content_hash(), cheaper.content_hash())
+
+    def test_floor_without_elevator(self):
+        criteria = FilterCriteria(min_rooms=0, max_floor_without_elevator=2)
+
+        def passes(floor, features):
+            details = {StandardFields.FLOOR: floor, StandardFields.FEATURES: features}
+            flat = FlatDetails(id=floor, title="Flat", link="", details=details,
+                               wbs_required=False, source="Mock")
+            return flat.meets_criteria(criteria)
+
+        self.assertTrue(passes("4. OG", "Balkon, Aufzug"))
+        self.assertFalse(passes("4. OG", "Balkon, kein Aufzug"))
+        self.assertTrue(passes("2. OG", "Balkon"))
+        # Unknown floor
+        self.assertTrue(passes("", "Balkon"))
+
+    def test_parse_floor(self):
+        floors = ["EG", "3. OG", "UG", "1. UG", "2. Untergeschoss", "Souterrain", "DG"]
+        self.assertEqual([parse_floor(floor) for floor in floors], [0, 3, -1, -1, -2, -1, None])
+
+    def test_max_rooms(self):
+        criteria = FilterCriteria(min_rooms=2, max_rooms=3)
+
+        def passes(rooms):
+            flat = FlatDetails(id=rooms, title="Flat", link="", details={StandardFields.ROOMS: rooms},
+                               wbs_required=False, source="Mock")
+            return flat.meets_criteria(criteria)
+
+        self.assertTrue(passes("3"))
+        self.assertTrue(passes("2,5"))
+        self.assertFalse(passes("4"))
+        self.assertFalse(passes("1"))
+        # Unknown room count
+        self.assertTrue(passes(""))
+
+    def test_max_rent(self):
+        criteria = FilterCriteria(min_rooms=0, max_rent=900)
+
+        def passes(rent):
+            flat = FlatDetails(id=rent, title="Flat", link="", details={StandardFields.RENT_WARM: rent},
+                               wbs_required=False, source="Mock")
+            return flat.meets_criteria(criteria)
+
+        self.assertTrue(passes("850,00 €"))
+        self.assertTrue(passes("900 €"))
+        self.assertFalse(passes("1.050,00 €"))
+        # Unparseable rent
+        self.assertTrue(passes("auf Anfrage"))
+
+    def test_min_area(self):
+        criteria = FilterCriteria(min_rooms=0, min_area=50)
+
+        def passes(area):
+            flat = FlatDetails(id=area, title="Flat", link="", details={StandardFields.AREA: area},
+                               wbs_required=False, source="Mock")
+            return flat.meets_criteria(criteria)
+
+        self.assertTrue(passes("50 m²"))
+        self.assertTrue(passes("62,5 m²"))
+        self.assertFalse(passes("48,9 m²"))
+        # Unknown area
+        self.assertTrue(passes(""))
+
+    def test_keywords(self):
+        criteria = FilterCriteria(min_rooms=0, include_keywords=["Balkon", "Terrasse"],
+                                  exclude_keywords=["Tausch"])
+
+        def passes(title, features=""):
+            flat = FlatDetails(id=title, title=title, link="", details={StandardFields.FEATURES: features},
+                               wbs_required=False, source="Mock")
+            return flat.meets_criteria(criteria)
+
+        self.assertTrue(passes("Altbau mit BALKON"))
+        self.assertTrue(passes("Altbau", "Einbauküche, Terrasse"))
+        self.assertFalse(passes("Altbau"))
+        self.assertFalse(passes("Wohnungstausch mit Balkon"))
+
+    def test_wbs_routes(self):
+        wbs_flat, other = make_flat("w"), make_flat("n")
+        wbs_flat.wbs_required = True
+        expected = {
+            "exclude": (None, "main"),
+            "include": ("main", "main"),
+            "separate_chat": ("wbs", "main"),
+        }
+        for policy, routes in expected.items():
+            with self.subTest(policy=policy):
+                self.assertEqual((wbs_route(wbs_flat, policy), wbs_route(other, policy)), routes)
+
+
+class FormatterTest(unittest.TestCase):
+    """Messages of MessageFormatter. Scraped texts must be escaped in them, or
+    Telegram rejects the HTML."""
+
+    def setUp(self):
+        self.flat = make_flat("a")
+        self.flat.title = "Wohnen <Neubau> & Garten"
+        self.flat.details[StandardFields.ADDRESS] = "Müller & Söhne Str. 1"
+
+    def test_flat_message_escapes_scraped_texts(self):
+        message = MessageFormatter.format_default_flat_message(self.flat)
+        self.assertIn("Wohnen &lt;Neubau&gt; &amp; Garten", message)
+        self.assertIn("Müller &amp; Söhne Str. 1", message)
+        self.assertNotIn("<Neubau>", message)
+
+    def test_maps_link_in_discord_markdown(self):
+        self.flat.details[StandardFields.ADDRESS] = "Teststr. 1"
+        markdown = html_to_markdown(MessageFormatter.format_default_flat_message(self.flat))
+        self.assertIn("[Teststr. 1](https://www.google.com/maps/search/?api=1&query=Teststr.%201)", markdown)
+        self.assertNotIn("<a ", markdown)
+
+    def test_price_change_escapes_title(self):
+        message = MessageFormatter.format_price_change_message(self.flat, 800, 850)
+        self.assertIn("Wohnen &lt;Neubau&gt; &amp; Garten", message)
+        self.assertNotIn("<Neubau>", message)
+
+    def test_daily_summary_escapes_titles(self):
+        message = MessageFormatter.format_daily_summary({"Mock": 1}, [self.flat])
+        self.assertIn("Wohnen &lt;Neubau&gt; &amp; Garten", message)
+        self.assertNotIn("<Neubau>", message)
+
+
+class ConfigTest(unittest.TestCase):
+    def setUp(self):
+        tmp = tempfile.TemporaryDirectory()
+        self.addCleanup(tmp.cleanup)
+        self.tmp = tmp.name
+
+    def test_monitor_interval_minimum(self):
+        with self.assertRaises(RuntimeError):
+            load_config(self.tmp, "interval-1", MONITOR_INTERVAL=1)
+        self.assertEqual(load_config(self.tmp, "interval-30", MONITOR_INTERVAL=30).monitor_interval, 30)
+
+    def test_telegram_timeout(self):
+        # Independent of MONITOR_INTERVAL
+        self.assertEqual(load_config(self.tmp, "timeout-default").telegram_timeout, 10)
+        self.assertEqual(load_config(self.tmp, "timeout-50", TELEGRAM_TIMEOUT=50).telegram_timeout, 50)
+        with self.assertRaises(RuntimeError):
+            load_config(self.tmp, "timeout-negative", TELEGRAM_TIMEOUT=-1)
+
+    def test_malformed_json_reports_position(self):
+        path = os.path.join(self.tmp, "malformed.json")
+        with open(path, "w") as f:
+            f.write('{\n  "BOT_TOKEN": "123456:TEST",\n  "CHAT_ID": "1",\n}\n')
+        with self.assertRaises(RuntimeError) as raised:
+            Config(path)
+        self.assertIn(path, str(raised.exception))
+        self.assertIn("line 4, column 1", str(raised.exception))
+
+    def test_socks_proxy_without_aiohttp_socks(self):
+        with mock.patch.dict(sys.modules, {"aiohttp_socks": None}):
+            with self.assertRaises(RuntimeError) as raised:
+                load_config(self.tmp, "socks", PROXY_URL="socks5://127.0.0.1:1080")
+        self.assertIn("aiohttp-socks", str(raised.exception))
+
+    def test_timezone_across_daylight_saving(self):
+        berlin = load_config(self.tmp, "timezone").timezone
+        # Clocks went from 02:00 CET to 03:00 CEST at 01:00 UTC on 30 March 2025
+        before = to_local(datetime(2025, 3, 30, 0, 30, tzinfo=timezone.utc), berlin)
+        after = to_local(datetime(2025, 3, 30, 1, 30, tzinfo=timezone.utc), berlin)
+        self.assertEqual(f"{before:%H:%M %Z}", "01:30 CET")
+        self.assertEqual(f"{after:%H:%M %Z}", "03:30 CEST")
+
+    def test_enabled_scrapers(self):
+        # Unset runs every built-in scraper
+        everything = FlatMonitor._create_scrapers(load_config(self.tmp, "all-scrapers"))
+        self.assertEqual(len(everything), 5)
+        for name in ("Degewo", "Gesobau", "Gewobag", "StadtUndLand"):
+            self.assertTrue(any(scraper.matches_name(name) for scraper in everything), name)
+        only_degewo = FlatMonitor._create_scrapers(
+            load_config(self.tmp, "degewo", ENABLED_SCRAPERS=["Degewo"])
+        )
+        self.assertEqual(len(only_degewo), 1)
+        self.assertTrue(only_degewo[0].matches_name("Degewo"))
+
+    def test_malformed_chat_ids(self):
+        malformed = [
+            {"CHAT_ID": "12ab"},
+            {"CHAT_ID": "@ab"},
+            {"CHAT_ID": ["1", "not a chat"]},
+            {"WBS_POLICY": "separate_chat", "WBS_CHAT_ID": "wbs-chat"},
+        ]
+        for index, settings in enumerate(malformed):
+            with self.subTest(settings=settings):
+                with self.assertRaises(RuntimeError) as raised:
+                    load_config(self.tmp, f"chat-{index}", **settings)
+                key = "WBS_CHAT_ID" if "WBS_CHAT_ID" in settings else "CHAT_ID"
+                self.assertIn(key, str(raised.exception))
+
+    def test_authorized_user_ids(self):
+        # A single ID, not its digits
+        single = load_config(self.tmp, "users-single", AUTHORIZED_USER_IDS="12345")
+        self.assertEqual(single.authorized_user_ids, ["12345"])
+        number = load_config(self.tmp, "users-number", AUTHORIZED_USER_IDS=12345)
+        self.assertEqual(number.authorized_user_ids, ["12345"])
+        listed = load_config(self.tmp, "users-list", AUTHORIZED_USER_IDS=[1, "2"])
+        self.assertEqual(listed.authorized_user_ids, ["1", "2"])
+
+    def test_wbs_policy(self):
+        separate = load_config(self.tmp, "wbs-separate", WBS_POLICY="separate_chat", WBS_CHAT_ID="-1003")
+        self.assertEqual(separate.wbs_chat_id, "-1003")
+        self.assertTrue(separate.criteria.allow_wbs)
+        self.assertFalse(load_config(self.tmp, "wbs-default").criteria.allow_wbs)
+        # ALLOW_WBS still works without WBS_POLICY
+        self.assertEqual(load_config(self.tmp, "wbs-legacy", ALLOW_WBS=True).wbs_policy, "include")
+        with self.assertRaises(RuntimeError):
+            load_config(self.tmp, "wbs-no-chat", WBS_POLICY="separate_chat")
+
+
+if __name__ == "__main__":
+    unittest.main()